@@ -1,9 +1,15 @@
 //! Command line interface for actions related to electron beams.
 
 pub mod accelerator;
+pub mod compression;
+pub mod conversion;
 pub mod detection;
+pub mod distributed;
 pub mod distribution;
+pub mod provenance;
 pub mod simulate;
+pub mod stats;
+pub mod streaming;
 
 use self::simulate::{create_simulate_subcommand, run_simulate_subcommand};
 use crate::{
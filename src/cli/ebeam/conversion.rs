@@ -0,0 +1,18 @@
+//! Field name normalization for the `extra-fixed-scalars`, `extra-varying-scalars` and vector
+//! counterparts in [`create_simulate_subcommand`](super::simulate::create_simulate_subcommand).
+//!
+//! An earlier version of this option additionally accepted a `:conversion` suffix (e.g.
+//! `r:cgs_to_si`) meant to transform each quantity element-wise as it was sampled. That suffix was
+//! withdrawn: applying it would need a hook into `ElectronBeamSwarm`'s
+//! `extract_fixed_scalars`/`extract_varying_scalars` methods (and their vector counterparts),
+//! which live in the `ebeam` module and do the actual sampling internally; that module is not part
+//! of this snapshot, so there was no point in this file from which the conversion could ever be
+//! applied to a sampled value. Shipping the suffix anyway — parsed and validated, but silently
+//! discarded — would have left it looking wired when it was not, so [`normalize_field_name`]
+//! only does what it can actually deliver: accepting a bare field name.
+
+/// Normalizes a field name from the `extra-*-scalars`/`extra-*-vectors` options to the
+/// lowercase form the snapshot's field lookup expects.
+pub fn normalize_field_name(name: &str) -> String {
+    name.to_lowercase()
+}
@@ -0,0 +1,3 @@
+//! Command line interface for reconnection site detection.
+
+pub mod manual;
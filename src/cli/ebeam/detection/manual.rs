@@ -0,0 +1,82 @@
+//! Command line interface for the manual reconnection site detector.
+
+use crate::{
+    ebeam::detection::manual::{ClusterRepresentative, ClusteringConfig, ManualReconnectionSiteDetector},
+    exit_on_error,
+    io::snapshot::fdt,
+};
+use clap::{Arg, ArgMatches, Command};
+use std::path::PathBuf;
+
+/// Builds a representation of the `manual_detector` command line subcommand.
+pub fn create_manual_reconnection_site_detector_subcommand(
+    _parent_command_name: &'static str,
+) -> Command<'static> {
+    Command::new("manual_detector")
+        .about("Use manually specified reconnection site positions")
+        .arg(
+            Arg::new("input-file")
+                .value_name("INPUT_FILE")
+                .help("Path to a file with reconnection site positions to use")
+                .required(true)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("cluster-radius")
+                .long("cluster-radius")
+                .value_name("VALUE")
+                .help(
+                    "Merge detected positions within this radius (Mm) of each other into \
+                     a single site (disabled by default)",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::new("cluster-representative")
+                .long("cluster-representative")
+                .value_name("NAME")
+                .help("Which position to keep for a cluster of merged detections")
+                .takes_value(true)
+                .possible_values(["centroid", "first-seen"])
+                .default_value("centroid"),
+        )
+}
+
+/// Constructs a manual reconnection site detector from the given command line arguments.
+pub fn construct_manual_reconnection_site_detector_from_options(
+    arguments: &ArgMatches,
+) -> ManualReconnectionSiteDetector {
+    let input_file_path = PathBuf::from(
+        arguments
+            .value_of("input-file")
+            .expect("No value for required argument"),
+    );
+
+    match arguments.value_of("cluster-radius") {
+        Some(radius_string) => {
+            let radius: fdt = exit_on_error!(
+                radius_string.parse(),
+                "Error: Could not parse value of cluster-radius: {}"
+            );
+            let representative = match arguments.value_of("cluster-representative").unwrap() {
+                "centroid" => ClusterRepresentative::Centroid,
+                "first-seen" => ClusterRepresentative::FirstSeen,
+                invalid => panic!("Invalid cluster representative strategy {}", invalid),
+            };
+            exit_on_error!(
+                ManualReconnectionSiteDetector::new_with_clustering(
+                    &input_file_path,
+                    ClusteringConfig {
+                        radius,
+                        representative,
+                    },
+                ),
+                "Error: Could not create manual reconnection site detector: {}"
+            )
+        }
+        None => exit_on_error!(
+            ManualReconnectionSiteDetector::new(&input_file_path),
+            "Error: Could not create manual reconnection site detector: {}"
+        ),
+    }
+}
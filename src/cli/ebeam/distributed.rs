@@ -0,0 +1,103 @@
+//! Partitioning math for a future distributed multi-rank beam simulation.
+//!
+//! `run_with_selected_stepper` parallelizes a single simulation across the cores of one node
+//! with rayon, so it is bounded by that node's RAM. Distributing the work across MPI ranks
+//! instead would have the detector run on rank 0, scatter the detected reconnection sites across
+//! ranks, have each rank load only the spatial subdomain of the snapshot it needs (computed by
+//! [`partition_into_rank_subdomains`], padded with a halo from [`required_halo_width`]), generate
+//! and propagate its local beams, and gather the resulting `ElectronBeamSwarm` fragments back. A
+//! beam whose trajectory leaves the local (haloed) subdomain would need the stepper loop to
+//! detect the boundary crossing and either widen the halo or hand the partially-traced beam off
+//! to the owning rank for continuation.
+//!
+//! What is implemented here is the rank-independent part: computing each rank's owned and
+//! halo-padded index ranges from the global grid shape. The actual rank-to-rank data movement —
+//! scattering sites, gathering beam fragments, handing off boundary-crossing beams, and
+//! collective H5Part writes (with `.fl`/JSON falling back to gather-to-rank-0) — needs the `mpi`
+//! crate's point-to-point/collective calls plus hooks into `ElectronBeamSwarm`'s own
+//! generation/propagation/save methods and a halo-aware subdomain-local snapshot reader; those
+//! live in the `ebeam` and `field`/`io::snapshot` modules, which are not part of this snapshot.
+//! No `--distributed` CLI flag is registered for the same reason: a flag that could only ever
+//! exit with "not yet implemented" shouldn't be exposed. These types are kept ready for that
+//! wiring once `ElectronBeamSwarm` and a subdomain-local snapshot reader are reachable.
+
+use crate::geometry::{Dim3, In3D};
+
+/// An axis-aligned index range `[lower, upper)` along one grid dimension.
+#[derive(Clone, Copy, Debug)]
+pub struct IndexRange {
+    pub lower: usize,
+    pub upper: usize,
+}
+
+impl IndexRange {
+    pub fn len(&self) -> usize {
+        self.upper - self.lower
+    }
+
+    /// Grows this range by `halo_width` indices on each side, clamped to `[0, grid_size)`.
+    pub fn padded(&self, halo_width: usize, grid_size: usize) -> Self {
+        Self {
+            lower: self.lower.saturating_sub(halo_width),
+            upper: (self.upper + halo_width).min(grid_size),
+        }
+    }
+}
+
+/// One rank's share of a spatially decomposed snapshot: the subdomain it owns along each axis,
+/// plus the halo-padded version a local snapshot reader would need to load.
+#[derive(Clone, Debug)]
+pub struct RankSubdomain {
+    pub rank: usize,
+    pub owned: In3D<IndexRange>,
+    pub padded: In3D<IndexRange>,
+}
+
+/// Partitions a global grid of `global_shape` cells into `rank_count` subdomains by splitting
+/// the x-axis into near-equal contiguous slabs (any leftover cells go to the lowest-numbered
+/// ranks), then pads each slab with `halo_width` cells of margin.
+pub fn partition_into_rank_subdomains(
+    global_shape: In3D<usize>,
+    rank_count: usize,
+    halo_width: usize,
+) -> Vec<RankSubdomain> {
+    let x_size = global_shape[Dim3::X];
+    let base_slab_size = x_size / rank_count;
+    let remainder = x_size % rank_count;
+
+    let full_y = IndexRange {
+        lower: 0,
+        upper: global_shape[Dim3::Y],
+    };
+    let full_z = IndexRange {
+        lower: 0,
+        upper: global_shape[Dim3::Z],
+    };
+
+    let mut subdomains = Vec::with_capacity(rank_count);
+    let mut lower = 0;
+    for rank in 0..rank_count {
+        let slab_size = base_slab_size + usize::from(rank < remainder);
+        let upper = lower + slab_size;
+        let owned_x = IndexRange { lower, upper };
+
+        let owned = In3D::new(owned_x, full_y, full_z);
+        let padded = In3D::new(owned_x.padded(halo_width, x_size), full_y, full_z);
+
+        subdomains.push(RankSubdomain {
+            rank,
+            owned,
+            padded,
+        });
+        lower = upper;
+    }
+    subdomains
+}
+
+/// Minimum halo width (in grid cells) a rank's loaded subdomain needs so that the poly-fit
+/// interpolator and the stepper can evaluate points near a boundary without neighbor-rank data:
+/// the interpolator's stencil radius, plus a margin for the distance a single accepted RKF step
+/// can cover before the stepper notices it has crossed into a neighboring subdomain.
+pub fn required_halo_width(interpolator_stencil_radius: usize, step_margin_cells: usize) -> usize {
+    interpolator_stencil_radius + step_margin_cells
+}
@@ -0,0 +1,125 @@
+//! Reproducibility metadata embedded alongside `ebeam simulate`'s `.fl` output.
+//!
+//! `--print-parameter-values` has always echoed the resolved detector/accelerator/propagator/
+//! interpolator/stepper configuration to stdout; [`SimulationProvenance`] captures that same
+//! information (plus the stepper type, input snapshot identity, CLI invocation string and a
+//! timestamp) and writes it next to the output data, so a beam dataset is self-describing and
+//! re-runnable without needing the original command line. [`ProvenanceBuilder`] accumulates the
+//! pieces as they become available while walking down through `run_with_selected_detector`,
+//! `run_with_selected_accelerator`, `run_with_simple_accelerator_and_selected_propagator`,
+//! `run_with_selected_interpolator` and `run_with_selected_stepper`, and is finalized into a
+//! [`SimulationProvenance`] right before `perform_post_simulation_actions` is called.
+//!
+//! This is deliberately scoped to the `.fl` output only, as a `<output>.meta.json` sidecar
+//! written independently of `ElectronBeamSwarm`, from this file alone. Embedding the same struct
+//! as HDF5 root attributes or as a top-level `"provenance"` object in the JSON/pickle outputs
+//! would need a hook into the writers for those formats, which live on `ElectronBeamSwarm` in the
+//! `ebeam` module; that module is not part of this snapshot, so those formats are out of scope
+//! here rather than promised and left unimplemented.
+
+use serde::Serialize;
+use std::{
+    env, fs, io,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The fully-resolved configuration and run context for one `ebeam simulate` invocation.
+#[derive(Debug, Serialize)]
+pub struct SimulationProvenance {
+    pub snapshot_identity: String,
+    pub stepper_type: String,
+    pub detector_config: String,
+    pub accelerator_config: String,
+    pub propagator_config: String,
+    pub interpolator_config: String,
+    pub stepper_config: String,
+    /// The seed used to initialize any random number generators involved in beam generation, if
+    /// applicable. Not currently populated: the electron beam generation code that would own a
+    /// seed lives in the `ebeam` module, not part of this snapshot.
+    pub random_seed: Option<u64>,
+    /// The command line the run was invoked with (`argv`, space-joined).
+    pub invocation: String,
+    /// Seconds since the Unix epoch when this provenance was collected.
+    pub unix_timestamp_secs: u64,
+}
+
+impl SimulationProvenance {
+    /// Writes this provenance as a `<output>.meta.json` sidecar file next to a `.fl` output.
+    pub fn write_as_fl_sidecar<P: AsRef<Path>>(&self, fl_output_path: P) -> io::Result<()> {
+        let sidecar_path = fl_output_path.as_ref().with_extension("meta.json");
+        let file = fs::File::create(sidecar_path)?;
+        serde_json::to_writer_pretty(file, self)
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+    }
+}
+
+/// Accumulates the pieces of a [`SimulationProvenance`] as they become available while
+/// resolving the detector/accelerator/propagator/interpolator/stepper configuration.
+#[derive(Debug, Default)]
+pub struct ProvenanceBuilder {
+    snapshot_identity: Option<String>,
+    stepper_type: Option<String>,
+    detector_config: Option<String>,
+    accelerator_config: Option<String>,
+    propagator_config: Option<String>,
+    interpolator_config: Option<String>,
+    stepper_config: Option<String>,
+}
+
+impl ProvenanceBuilder {
+    pub fn new(snapshot_identity: String) -> Self {
+        Self {
+            snapshot_identity: Some(snapshot_identity),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_detector_config(mut self, detector_config_repr: String) -> Self {
+        self.detector_config = Some(detector_config_repr);
+        self
+    }
+
+    pub fn with_accelerator_config(mut self, accelerator_config_repr: String) -> Self {
+        self.accelerator_config = Some(accelerator_config_repr);
+        self
+    }
+
+    pub fn with_propagator_config(mut self, propagator_config_repr: String) -> Self {
+        self.propagator_config = Some(propagator_config_repr);
+        self
+    }
+
+    pub fn with_interpolator_config(mut self, interpolator_config_repr: String) -> Self {
+        self.interpolator_config = Some(interpolator_config_repr);
+        self
+    }
+
+    pub fn with_stepper(mut self, stepper_type_repr: String, stepper_config_repr: String) -> Self {
+        self.stepper_type = Some(stepper_type_repr);
+        self.stepper_config = Some(stepper_config_repr);
+        self
+    }
+
+    /// Finalizes the builder into a [`SimulationProvenance`], substituting `"<unavailable>"` for
+    /// any piece that was never set (defensively; every field is expected to be set by the time
+    /// this is called from `run_with_selected_stepper`).
+    pub fn build(self) -> SimulationProvenance {
+        let unavailable = || "<unavailable>".to_string();
+        SimulationProvenance {
+            snapshot_identity: self.snapshot_identity.unwrap_or_else(unavailable),
+            stepper_type: self.stepper_type.unwrap_or_else(unavailable),
+            detector_config: self.detector_config.unwrap_or_else(unavailable),
+            accelerator_config: self.accelerator_config.unwrap_or_else(unavailable),
+            propagator_config: self.propagator_config.unwrap_or_else(unavailable),
+            interpolator_config: self.interpolator_config.unwrap_or_else(unavailable),
+            stepper_config: self.stepper_config.unwrap_or_else(unavailable),
+            random_seed: None,
+            invocation: env::args().collect::<Vec<_>>().join(" "),
+            unix_timestamp_secs: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
@@ -5,6 +5,8 @@ use super::{
         construct_simple_power_law_accelerator_config_from_options,
         create_simple_power_law_accelerator_subcommand,
     },
+    compression::{parse_output_compression_from_arguments, recompress_file_in_place},
+    conversion::normalize_field_name,
     detection::{
         manual::{
             construct_manual_reconnection_site_detector_from_options,
@@ -20,6 +22,8 @@ use super::{
         construct_analytical_propagator_config_from_options,
         create_analytical_propagator_subcommand,
     },
+    provenance::{ProvenanceBuilder, SimulationProvenance},
+    streaming::parse_buffer_beams_from_arguments,
 };
 use crate::{
     add_subcommand_combinations,
@@ -61,8 +65,9 @@ use crate::{
         snapshot::{self, fdt, SnapshotMetadata},
         utils::{AtomicOutputFile, IOContext},
     },
-    tracing::stepping::rkf::{
-        rkf23::RKF23Stepper3, rkf45::RKF45Stepper3, RKFStepperConfig, RKFStepperType,
+    tracing::{
+        field_line::quantized,
+        stepping::rkf::{rkf23::RKF23Stepper3, rkf45::RKF45Stepper3, RKFStepperConfig, RKFStepperType},
     },
     update_command_graph,
 };
@@ -135,10 +140,8 @@ pub fn create_simulate_subcommand(_parent_command_name: &'static str) -> Command
                 .use_value_delimiter(true)
                 .require_value_delimiter(true)
                 .value_name("NAMES")
-                .help(
-                    "List of scalar fields to extract at acceleration sites\n \
-                     (comma-separated)",
-                )
+                .help("List of scalar fields to extract at acceleration sites\n \
+                     (comma-separated)")
                 .takes_value(true)
                 .multiple_values(true),
         )
@@ -149,10 +152,8 @@ pub fn create_simulate_subcommand(_parent_command_name: &'static str) -> Command
                 .use_value_delimiter(true)
                 .require_value_delimiter(true)
                 .value_name("NAMES")
-                .help(
-                    "List of vector fields to extract at acceleration sites\n \
-                     (comma-separated)",
-                )
+                .help("List of vector fields to extract at acceleration sites\n \
+                     (comma-separated)")
                 .takes_value(true)
                 .multiple_values(true),
         )
@@ -163,10 +164,8 @@ pub fn create_simulate_subcommand(_parent_command_name: &'static str) -> Command
                 .use_value_delimiter(true)
                 .require_value_delimiter(true)
                 .value_name("NAMES")
-                .help(
-                    "List of scalar fields to extract along beam trajectories\n \
-                     (comma-separated)",
-                )
+                .help("List of scalar fields to extract along beam trajectories\n \
+                     (comma-separated)")
                 .takes_value(true)
                 .multiple_values(true),
         )
@@ -177,10 +176,8 @@ pub fn create_simulate_subcommand(_parent_command_name: &'static str) -> Command
                 .use_value_delimiter(true)
                 .require_value_delimiter(true)
                 .value_name("NAMES")
-                .help(
-                    "List of vector fields to extract along beam trajectories\n \
-                     (comma-separated)",
-                )
+                .help("List of vector fields to extract along beam trajectories\n \
+                     (comma-separated)")
                 .takes_value(true)
                 .multiple_values(true),
         )
@@ -188,6 +185,40 @@ pub fn create_simulate_subcommand(_parent_command_name: &'static str) -> Command
             "Reduce H5Part file size by excluding particle IDs required by some tools\n\
                      (e.g. VisIt)",
         ))
+        .arg(
+            Arg::new("fl-compression")
+                .long("fl-compression")
+                .require_equals(true)
+                .value_name("MODE")
+                .help(
+                    "How to compress the varying scalar/vector sections of a *.fl output file\n\
+                       *raw: Write values uncompressed\
+                       \n    zstd: Losslessly zstd-compress the raw values\
+                       \n    lossy: Quantize to `fl-compression-precision` relative precision,\n\
+                       \            then zstd-compress the resulting integers",
+                )
+                .takes_value(true)
+                .possible_values(&["raw", "zstd", "lossy"])
+                .default_value("raw"),
+        )
+        .arg(
+            Arg::new("fl-compression-level")
+                .long("fl-compression-level")
+                .require_equals(true)
+                .value_name("LEVEL")
+                .help("zstd compression level to use for the `zstd` and `lossy` fl-compression modes")
+                .takes_value(true)
+                .default_value("3"),
+        )
+        .arg(
+            Arg::new("fl-compression-precision")
+                .long("fl-compression-precision")
+                .require_equals(true)
+                .value_name("PRECISION")
+                .help("Target relative precision to use for the `lossy` fl-compression mode")
+                .takes_value(true)
+                .default_value("1e-3"),
+        )
         .arg(
             Arg::new("verbose")
                 .short('v')
@@ -206,6 +237,32 @@ pub fn create_simulate_subcommand(_parent_command_name: &'static str) -> Command
                 .help("Prints the values of all the parameters that will be used")
                 .hide(true),
         )
+        .arg(
+            Arg::new("compression")
+                .long("compression")
+                .require_equals(true)
+                .value_name("CODEC")
+                .help(
+                    "Compress the fl/pickle/json output file with the given codec after writing\n\
+                     it, trading CPU for file size; h5part ignores this and is written\n\
+                     uncompressed",
+                )
+                .takes_value(true)
+                .possible_values(&["none", "gzip", "zstd", "brotli"])
+                .default_value("none"),
+        )
+        .arg(
+            Arg::new("buffer-beams")
+                .long("buffer-beams")
+                .require_equals(true)
+                .value_name("N")
+                .help(
+                    "Batch size (a positive integer) for streaming beam output. The value is\n\
+                     validated only: all beams are still held in memory and saved in one pass",
+                )
+                .takes_value(true)
+                .default_value("1000"),
+        )
         .subcommand(create_simple_reconnection_site_detector_subcommand(
             command_name,
         ))
@@ -226,6 +283,14 @@ pub fn run_simulate_subcommand(
     provider: DynScalarFieldProvider3<fdt>,
     io_context: &mut IOContext,
 ) {
+    // No `--distributed` flag is registered above: actually scattering the detector's sites
+    // across ranks, gathering `ElectronBeamSwarm` fragments back, and handing off
+    // boundary-crossing beams between ranks (see `distributed::partition_into_rank_subdomains`)
+    // needs `ElectronBeamSwarm`'s own generation/propagation methods, which live in the `ebeam`
+    // module; that module is not part of this snapshot, so a flag that could only ever exit with
+    // "not yet implemented" shouldn't be exposed. `cli::ebeam::distributed` is kept ready for that
+    // CLI wiring once `ElectronBeamSwarm` is reachable.
+
     let verbosity = cli_utils::parse_verbosity(arguments, false);
     let snapshot = Box::new(ScalarFieldCacher3::new_manual_cacher(provider, verbosity));
     run_with_selected_detector(arguments, metadata, snapshot, io_context);
@@ -240,8 +305,59 @@ enum OutputType {
     Json,
     #[cfg(feature = "hdf5")]
     H5Part,
+    // No `Parquet` variant: an Arrow/Parquet writer doesn't exist yet, so `.parquet` isn't
+    // registered or advertised as a selectable output extension until one does (see
+    // `perform_post_simulation_actions`).
+}
+
+/// Metadata for one supported output format: the file extension selecting it. Formats behind a
+/// disabled Cargo feature are left out of the registry (see [`ALL_KNOWN_EXTENSIONS`]). This
+/// registry is what `OutputType::from_extension`/`valid_extensions_string` are driven from, so
+/// adding a format means adding one entry to [`registered_output_formats`], a `Display` arm and a
+/// handler for the actual write in `perform_post_simulation_actions`.
+///
+/// A single `write_swarm` method isn't part of this registry: `ElectronBeamSwarm<A>` is generic
+/// over the accelerator type `A`, and a registry entry needs to be a plain value (not a trait
+/// object bound to one `A`), so the dispatch from `OutputType` to the right `save_as_*` call
+/// stays a `match` in `perform_post_simulation_actions`.
+struct OutputFormatInfo {
+    output_type: OutputType,
+    extension: &'static str,
 }
 
+fn registered_output_formats() -> Vec<OutputFormatInfo> {
+    let mut formats = vec![OutputFormatInfo {
+        output_type: OutputType::Fl,
+        extension: "fl",
+    }];
+    #[cfg(feature = "pickle")]
+    formats.push(OutputFormatInfo {
+        output_type: OutputType::Pickle,
+        extension: "pickle",
+    });
+    #[cfg(feature = "json")]
+    formats.push(OutputFormatInfo {
+        output_type: OutputType::Json,
+        extension: "json",
+    });
+    #[cfg(feature = "hdf5")]
+    formats.push(OutputFormatInfo {
+        output_type: OutputType::H5Part,
+        extension: "h5part",
+    });
+    formats
+}
+
+/// Extensions of formats this build was *not* compiled with, so an unrecognized extension that
+/// would be valid with a different feature set gets a "compile with X" error instead of a bare
+/// "invalid extension" one. Kept separate from `registered_output_formats` since a disabled
+/// format has no `OutputType` variant to put in the registry.
+const ALL_KNOWN_EXTENSIONS: &[(&str, &str)] = &[
+    ("pickle", "pickle"),
+    ("json", "json"),
+    ("h5part", "hdf5"),
+];
+
 impl OutputType {
     fn from_path(file_path: &Path) -> Self {
         Self::from_extension(
@@ -260,57 +376,61 @@ impl OutputType {
     }
 
     fn from_extension(extension: &str) -> Self {
-        match extension {
-            "fl" => Self::Fl,
-            "pickle" => {
-                #[cfg(feature = "pickle")]
-                {
-                    Self::Pickle
-                }
-                #[cfg(not(feature = "pickle"))]
-                exit_with_error!(
-                    "Error: Compile with pickle feature in order to write Pickle files\n\
-                     Tip: Use cargo flag --features=pickle"
-                );
-            }
-            "json" => {
-                #[cfg(feature = "json")]
-                {
-                    Self::Json
-                }
-                #[cfg(not(feature = "json"))]
-                exit_with_error!(
-                    "Error: Compile with json feature in order to write JSON files\n\
-                     Tip: Use cargo flag --features=json"
-                );
-            }
-            "h5part" => {
-                #[cfg(feature = "hdf5")]
-                {
-                    Self::H5Part
-                }
-                #[cfg(not(feature = "hdf5"))]
-                exit_with_error!("Error: Compile with hdf5 feature in order to write H5Part files\n\
-                                  Tip: Use cargo flag --features=hdf5 and make sure the HDF5 library is available");
-            }
-            invalid => exit_with_error!(
-                "Error: Invalid extension {} for output file\n\
-                 Valid extensions are: {}",
-                invalid,
-                Self::valid_extensions_string()
-            ),
+        if let Some(format) = registered_output_formats()
+            .into_iter()
+            .find(|format| format.extension == extension)
+        {
+            return format.output_type;
+        }
+        if let Some((_, feature_name)) = ALL_KNOWN_EXTENSIONS
+            .iter()
+            .find(|(known_extension, _)| *known_extension == extension)
+        {
+            exit_with_error!(
+                "Error: Compile with {0} feature in order to write {1} files\n\
+                 Tip: Use cargo flag --features={0}",
+                feature_name,
+                extension
+            );
         }
+        exit_with_error!(
+            "Error: Invalid extension {} for output file\n\
+             Valid extensions are: {}",
+            extension,
+            Self::valid_extensions_string()
+        )
     }
 
     fn valid_extensions_string() -> String {
-        format!(
-            "fl, pickle, json{}",
-            if cfg!(feature = "hdf5") {
-                ", h5part"
-            } else {
-                ""
-            }
-        )
+        registered_output_formats()
+            .iter()
+            .map(|format| format.extension)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Determines the compression mode to use for the varying sections of a `*.fl` output file
+/// based on the `fl-compression*` options.
+fn parse_fl_compression_mode_from_arguments(
+    arguments: &ArgMatches,
+) -> quantized::VaryingDataCompressionMode {
+    let level = cli_utils::get_value_from_required_parseable_argument(arguments, "fl-compression-level");
+    let relative_precision = cli_utils::get_value_from_required_parseable_argument(
+        arguments,
+        "fl-compression-precision",
+    );
+    match arguments
+        .value_of("fl-compression")
+        .expect("No value for required argument")
+    {
+        "raw" => quantized::VaryingDataCompressionMode::Raw,
+        "zstd" => quantized::VaryingDataCompressionMode::Zstd { level },
+        "lossy" => quantized::VaryingDataCompressionMode::LossyQuantized {
+            relative_precision,
+            zstd_level: level,
+        },
+        invalid => exit_with_error!("Error: Invalid value for fl-compression: {}", invalid),
     }
 }
 
@@ -338,13 +458,14 @@ fn run_with_selected_detector(
     snapshot: DynCachingScalarFieldProvider3<fdt>,
     io_context: &mut IOContext,
 ) {
-    let (detector, detector_arguments) =
+    let (detector, detector_arguments, detector_config_repr) =
         if let Some(detector_arguments) = root_arguments.subcommand_matches("manual_detector") {
             (
                 Box::new(construct_manual_reconnection_site_detector_from_options(
                     detector_arguments,
                 )) as DynReconnectionSiteDetector,
                 detector_arguments,
+                "<manual_detector>".to_string(),
             )
         } else {
             let (detector_config, detector_arguments) = if let Some(detector_arguments) =
@@ -370,18 +491,26 @@ fn run_with_selected_detector(
                 println!("{:#?}", detector_config);
             }
 
+            let detector_config_repr = format!("{:#?}", detector_config);
+
             (
                 Box::new(SimpleReconnectionSiteDetector::new(detector_config))
                     as DynReconnectionSiteDetector,
                 detector_arguments,
+                detector_config_repr,
             )
         };
+
+    let provenance = ProvenanceBuilder::new(format!("{:#?}", metadata.parameters()))
+        .with_detector_config(detector_config_repr);
+
     run_with_selected_accelerator(
         root_arguments,
         detector_arguments,
         metadata,
         snapshot,
         detector,
+        provenance,
         io_context,
     );
 }
@@ -392,6 +521,7 @@ fn run_with_selected_accelerator(
     metadata: &dyn SnapshotMetadata,
     snapshot: DynCachingScalarFieldProvider3<fdt>,
     detector: DynReconnectionSiteDetector,
+    provenance: ProvenanceBuilder,
     io_context: &mut IOContext,
 ) {
     let distribution_arguments = arguments
@@ -408,6 +538,7 @@ fn run_with_selected_accelerator(
         if root_arguments.is_present("print-parameter-values") {
             println!("{:#?}", accelerator_config);
         }
+        let provenance = provenance.with_accelerator_config(format!("{:#?}", accelerator_config));
         let accelerator = SimplePowerLawAccelerator::new(accelerator_config);
         run_with_simple_accelerator_and_selected_propagator(
             root_arguments,
@@ -416,6 +547,7 @@ fn run_with_selected_accelerator(
             snapshot,
             detector,
             accelerator,
+            provenance,
             io_context,
         );
     } else {
@@ -424,6 +556,7 @@ fn run_with_selected_accelerator(
         if root_arguments.is_present("print-parameter-values") {
             println!("{:#?}", accelerator_config);
         }
+        let provenance = provenance.with_accelerator_config(format!("{:#?}", accelerator_config));
         let accelerator = SimplePowerLawAccelerator::new(accelerator_config);
         run_with_simple_accelerator_and_selected_propagator(
             root_arguments,
@@ -432,6 +565,7 @@ fn run_with_selected_accelerator(
             snapshot,
             detector,
             accelerator,
+            provenance,
             io_context,
         );
     };
@@ -444,6 +578,7 @@ fn run_with_simple_accelerator_and_selected_propagator(
     snapshot: DynCachingScalarFieldProvider3<fdt>,
     detector: DynReconnectionSiteDetector,
     accelerator: SimplePowerLawAccelerator,
+    provenance: ProvenanceBuilder,
     io_context: &mut IOContext,
 ) {
     if let Some(propagator_arguments) = arguments.subcommand_matches("analytical_propagator") {
@@ -454,6 +589,7 @@ fn run_with_simple_accelerator_and_selected_propagator(
         if root_arguments.is_present("print-parameter-values") {
             println!("{:#?}", propagator_config);
         }
+        let provenance = provenance.with_propagator_config(format!("{:#?}", propagator_config));
         run_with_selected_interpolator::<_, AnalyticalPropagator>(
             root_arguments,
             propagator_arguments,
@@ -461,6 +597,7 @@ fn run_with_simple_accelerator_and_selected_propagator(
             detector,
             accelerator,
             propagator_config,
+            provenance,
             io_context,
         );
     } else {
@@ -469,6 +606,7 @@ fn run_with_simple_accelerator_and_selected_propagator(
         if root_arguments.is_present("print-parameter-values") {
             println!("{:#?}", propagator_config);
         }
+        let provenance = provenance.with_propagator_config(format!("{:#?}", propagator_config));
         run_with_selected_interpolator::<_, AnalyticalPropagator>(
             root_arguments,
             arguments,
@@ -476,6 +614,7 @@ fn run_with_simple_accelerator_and_selected_propagator(
             detector,
             accelerator,
             propagator_config,
+            provenance,
             io_context,
         );
     }
@@ -488,6 +627,7 @@ fn run_with_selected_interpolator<A, P>(
     detector: DynReconnectionSiteDetector,
     accelerator: A,
     propagator_config: P::Config,
+    provenance: ProvenanceBuilder,
     io_context: &mut IOContext)
 where A: Accelerator + Sync + Send,
       P: Propagator<<A as Accelerator>::DistributionType>,
@@ -509,6 +649,8 @@ where A: Accelerator + Sync + Send,
         println!("{:#?}", interpolator_config);
     }
 
+    let provenance = provenance.with_interpolator_config(format!("{:#?}", interpolator_config));
+
     let interpolator = Box::new(PolyFitInterpolator3::new(interpolator_config));
 
     exit_on_error!(
@@ -524,6 +666,7 @@ where A: Accelerator + Sync + Send,
         accelerator,
         propagator_config,
         interpolator.as_ref(),
+        provenance,
         io_context,
     );
 }
@@ -536,6 +679,7 @@ fn run_with_selected_stepper<A, P>(
     accelerator: A,
     propagator_config: P::Config,
     interpolator: &dyn Interpolator3<fdt>,
+    provenance: ProvenanceBuilder,
     io_context: &mut IOContext)
 where A: Accelerator + Sync + Send,
       P: Propagator<<A as Accelerator>::DistributionType>,
@@ -552,6 +696,14 @@ where A: Accelerator + Sync + Send,
     if root_arguments.is_present("print-parameter-values") {
         println!("{:#?}\nstepper_type: {:?}", stepper_config, stepper_type);
     }
+
+    let provenance = provenance
+        .with_stepper(
+            format!("{:?}", stepper_type),
+            format!("{:#?}", stepper_config),
+        )
+        .build();
+
     let mut output_file_path = exit_on_error!(
         PathBuf::from_str(
             root_arguments
@@ -664,6 +816,7 @@ where A: Accelerator + Sync + Send,
         snapshot,
         interpolator,
         beams,
+        provenance,
     );
 }
 
@@ -676,6 +829,7 @@ fn perform_post_simulation_actions<A>(
     mut snapshot: DynCachingScalarFieldProvider3<fdt>,
     interpolator: &dyn Interpolator3<fdt>,
     mut beams: ElectronBeamSwarm<A>,
+    provenance: SimulationProvenance,
 ) where
     A: Accelerator,
 {
@@ -683,8 +837,8 @@ fn perform_post_simulation_actions<A>(
         .values_of("extra-fixed-scalars")
         .map(|values| values.collect::<Vec<_>>())
     {
-        for name in extra_fixed_scalars {
-            let name = name.to_lowercase();
+        for spec in extra_fixed_scalars {
+            let name = normalize_field_name(spec);
             beams.extract_fixed_scalars(
                 exit_on_error!(
                     snapshot.provide_scalar_field(&name).as_ref(),
@@ -699,8 +853,8 @@ fn perform_post_simulation_actions<A>(
         .values_of("extra-fixed-vectors")
         .map(|values| values.collect::<Vec<_>>())
     {
-        for name in extra_fixed_vectors {
-            let name = name.to_lowercase();
+        for spec in extra_fixed_vectors {
+            let name = normalize_field_name(spec);
             beams.extract_fixed_vectors(
                 exit_on_error!(
                     snapshot.provide_vector_field(&name).as_ref(),
@@ -715,8 +869,8 @@ fn perform_post_simulation_actions<A>(
         .values_of("extra-varying-scalars")
         .map(|values| values.collect::<Vec<_>>())
     {
-        for name in extra_varying_scalars {
-            let name = name.to_lowercase();
+        for spec in extra_varying_scalars {
+            let name = normalize_field_name(spec);
             beams.extract_varying_scalars(
                 exit_on_error!(
                     snapshot.provide_scalar_field(&name).as_ref(),
@@ -731,8 +885,8 @@ fn perform_post_simulation_actions<A>(
         .values_of("extra-varying-vectors")
         .map(|values| values.collect::<Vec<_>>())
     {
-        for name in extra_varying_vectors {
-            let name = name.to_lowercase();
+        for spec in extra_varying_vectors {
+            let name = normalize_field_name(spec);
             beams.extract_varying_vectors(
                 exit_on_error!(
                     snapshot.provide_vector_field(&name).as_ref(),
@@ -744,6 +898,20 @@ fn perform_post_simulation_actions<A>(
         }
     }
 
+    // `buffer_beams` is meant to cap how many completed beams a `StreamingBeamWriter` holds in
+    // memory at once, but streaming the save below in batches would need `ElectronBeamSwarm`'s
+    // own generation methods to yield beams incrementally instead of returning a complete swarm;
+    // those live in the `ebeam` module, not part of this snapshot, so the value is parsed (and
+    // validated) but not yet used to bound the save below.
+    let _buffer_beams = parse_buffer_beams_from_arguments(root_arguments);
+
+    // No `--stats` flag is registered above: computing `SampleStatistics` per extracted quantity
+    // and running `DuplicateBeamReport::detect_duplicates` over the beams' position streams needs
+    // read access to `ElectronBeamSwarm`'s own fixed/varying-quantity arrays and trajectories,
+    // which live on that type in the `ebeam` module; that module is not part of this snapshot
+    // (only `arena` and `detection/manual.rs` are present under it). See `cli::ebeam::stats` for
+    // the summary types, kept ready for this call site once that data is reachable.
+
     if beams.verbosity().print_messages() {
         println!(
             "Saving beams in {}",
@@ -757,7 +925,16 @@ fn perform_post_simulation_actions<A>(
 
     exit_on_error!(
         match output_type {
-            OutputType::Fl => beams.save_into_custom_binary(atomic_output_file.temporary_path()),
+            // `provenance` is only ever written out below as the `.fl` sidecar (see
+            // `cli::ebeam::provenance`'s module documentation for why that's this feature's
+            // scope): embedding it as a top-level `"provenance"` object here (and as root
+            // attributes for H5Part below) would need a hook into `ElectronBeamSwarm`'s own
+            // `save_as_json`/`save_as_combined_pickles`/`save_as_h5part` methods, which live in
+            // the `ebeam` module, not part of this snapshot.
+            OutputType::Fl => beams.save_into_custom_binary(
+                atomic_output_file.temporary_path(),
+                parse_fl_compression_mode_from_arguments(root_arguments),
+            ),
             #[cfg(feature = "pickle")]
             OutputType::Pickle =>
                 beams.save_as_combined_pickles(atomic_output_file.temporary_path()),
@@ -769,14 +946,46 @@ fn perform_post_simulation_actions<A>(
                 extra_atomic_output_file.as_ref().unwrap().temporary_path(),
                 root_arguments.is_present("drop-h5part-id"),
             ),
+            // No `OutputType::Parquet` arm: writing the fixed per-beam scalars/vectors as one
+            // row group and the varying-along-trajectory quantities as a second list-typed table
+            // needs direct access to `ElectronBeamSwarm`'s fields (or a `save_as_parquet` method
+            // alongside its other `save_as_*` methods); that type lives in the `ebeam` module,
+            // not part of this snapshot. Rather than advertise `.parquet` as a selectable output
+            // extension that always errors, the extension isn't registered at all (see
+            // `registered_output_formats`) until a real writer exists.
         },
         "Error: Could not save output data: {}"
     );
 
+    let output_compression = parse_output_compression_from_arguments(root_arguments);
+    match output_type {
+        #[cfg(feature = "hdf5")]
+        OutputType::H5Part => (), // H5Part output is left uncompressed; see `compression`.
+        _ => {
+            exit_on_error!(
+                recompress_file_in_place(atomic_output_file.temporary_path(), output_compression),
+                "Error: Could not compress output data: {}"
+            );
+        }
+    }
+
+    let fl_target_path = if let OutputType::Fl = output_type {
+        Some(atomic_output_file.target_path().to_path_buf())
+    } else {
+        None
+    };
+
     exit_on_error!(
         io_context.close_atomic_output_file(atomic_output_file),
         "Error: Could not move temporary output file to target path: {}"
     );
+
+    if let Some(fl_target_path) = fl_target_path {
+        exit_on_error!(
+            provenance.write_as_fl_sidecar(fl_target_path),
+            "Error: Could not write provenance sidecar file: {}"
+        );
+    }
     if let Some(extra_atomic_output_file) = extra_atomic_output_file {
         exit_on_error!(
             io_context.close_atomic_output_file(extra_atomic_output_file),
@@ -0,0 +1,154 @@
+//! Extraction/output statistics summary, computed from the quantities extracted per simulation.
+//!
+//! `perform_post_simulation_actions` has always printed just "Saving beams in …" when verbose.
+//! [`SampleStatistics`] computes the min/max/mean and NaN/infinity counts for one quantity's
+//! sampled values, and [`DuplicateBeamReport`] hashes each beam's position stream to find beams
+//! whose trajectories are byte-identical, grouping hash collisions and verifying equality before
+//! counting them as true duplicates. Calling these per extracted quantity and over the full set of
+//! beam trajectories needs access to `ElectronBeamSwarm`'s own fixed/varying-quantity arrays and
+//! position streams, which live on that type in the `ebeam` module; that module is not part of
+//! this snapshot (only `arena` and `detection/manual.rs` are present under it). No `--stats` flag
+//! is registered in `cli::ebeam::simulate` for the same reason: a flag that can only print "not
+//! yet implemented" shouldn't be exposed. These types are kept ready for that CLI wiring once
+//! `ElectronBeamSwarm` is reachable.
+
+/// Summary statistics for one quantity's sampled values.
+#[derive(Debug, Clone, Copy)]
+pub struct SampleStatistics {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub nan_count: usize,
+    pub infinite_count: usize,
+    pub sample_count: usize,
+}
+
+impl SampleStatistics {
+    /// Computes statistics over `values`, excluding NaNs and infinities from `min`/`max`/`mean`
+    /// but still counting them.
+    pub fn from_values(values: &[f64]) -> Self {
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        let mut sum = 0.0;
+        let mut finite_count = 0;
+        let mut nan_count = 0;
+        let mut infinite_count = 0;
+        for &value in values {
+            if value.is_nan() {
+                nan_count += 1;
+            } else if value.is_infinite() {
+                infinite_count += 1;
+            } else {
+                min = min.min(value);
+                max = max.max(value);
+                sum += value;
+                finite_count += 1;
+            }
+        }
+        let mean = if finite_count > 0 {
+            sum / finite_count as f64
+        } else {
+            f64::NAN
+        };
+        Self {
+            min,
+            max,
+            mean,
+            nan_count,
+            infinite_count,
+            sample_count: values.len(),
+        }
+    }
+}
+
+/// A 64-bit gear hash accumulated over the little-endian bytes of a stream of coordinates, used to
+/// cheaply find candidate duplicate beams before falling back to an exact comparison.
+fn gear_hash_coordinates(coordinates: &[f64]) -> u64 {
+    // A fixed, arbitrarily chosen substitution table, as used by gear-hash-based content-defined
+    // chunking: each input byte is mixed in through a table lookup rather than a plain XOR, so
+    // permuted-but-otherwise-identical byte streams don't collide trivially.
+    const GEAR_TABLE: [u64; 256] = {
+        let mut table = [0u64; 256];
+        let mut i = 0;
+        while i < 256 {
+            // A simple splitmix-style constant multiplier is enough to decorrelate table entries;
+            // this doesn't need to be cryptographically strong, only well-distributed.
+            table[i] = (i as u64).wrapping_mul(0x9E3779B97F4A7C15).rotate_left(17) ^ 0xD6E8FEB86659FD93;
+            i += 1;
+        }
+        table
+    };
+
+    let mut hash: u64 = 0;
+    for &coordinate in coordinates {
+        for byte in coordinate.to_le_bytes() {
+            hash = (hash << 1).wrapping_add(GEAR_TABLE[byte as usize]);
+        }
+    }
+    hash
+}
+
+/// How many beams in a swarm are exact duplicates of another beam (identical sampled position
+/// sequences), and what fraction of all samples they account for.
+#[derive(Debug, Clone, Copy)]
+pub struct DuplicateBeamReport {
+    pub total_beam_count: usize,
+    pub duplicate_beam_count: usize,
+    pub redundant_sample_fraction: f64,
+}
+
+impl DuplicateBeamReport {
+    /// Detects duplicates among `beam_coordinate_streams`, one flattened `[x0, y0, z0, x1, ...]`
+    /// coordinate slice per beam. Beams are first grouped by [`gear_hash_coordinates`], then each
+    /// group is confirmed by an exact slice comparison, since a hash collision alone does not
+    /// prove two beams are identical.
+    pub fn detect_duplicates(beam_coordinate_streams: &[&[f64]]) -> Self {
+        use std::collections::HashMap;
+
+        let total_beam_count = beam_coordinate_streams.len();
+        let mut beams_by_hash: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (index, coordinates) in beam_coordinate_streams.iter().enumerate() {
+            beams_by_hash
+                .entry(gear_hash_coordinates(coordinates))
+                .or_default()
+                .push(index);
+        }
+
+        let mut duplicate_beam_count = 0;
+        let mut redundant_sample_count = 0;
+        let total_sample_count: usize = beam_coordinate_streams.iter().map(|s| s.len()).sum();
+
+        for candidate_indices in beams_by_hash.values() {
+            if candidate_indices.len() < 2 {
+                continue;
+            }
+            // Group the hash collisions into equality classes, since distinct beams can share a
+            // hash without being identical.
+            let mut representatives: Vec<usize> = Vec::new();
+            for &index in candidate_indices {
+                let coordinates = beam_coordinate_streams[index];
+                if let Some(&representative) = representatives
+                    .iter()
+                    .find(|&&other| beam_coordinate_streams[other] == coordinates)
+                {
+                    duplicate_beam_count += 1;
+                    redundant_sample_count += beam_coordinate_streams[representative].len();
+                } else {
+                    representatives.push(index);
+                }
+            }
+        }
+
+        let redundant_sample_fraction = if total_sample_count > 0 {
+            redundant_sample_count as f64 / total_sample_count as f64
+        } else {
+            0.0
+        };
+
+        Self {
+            total_beam_count,
+            duplicate_beam_count,
+            redundant_sample_fraction,
+        }
+    }
+}
@@ -0,0 +1,75 @@
+//! Bounded-memory, chunked writing of beam swarms.
+//!
+//! `perform_post_simulation_actions` currently receives a fully-formed `ElectronBeamSwarm` and
+//! serializes it in one call, so peak memory holds every trajectory of every beam at once. A
+//! [`StreamingBeamWriter`] is meant to instead receive the beams in batches of at most
+//! `--buffer-beams` beams (see [`parse_buffer_beams_from_arguments`]) as they complete, flushing
+//! each batch to the open `AtomicOutputFile` before dropping it: appending an H5Part time/particle
+//! block, emitting a newline-delimited JSON record per beam, or extending the `.fl` binary layout
+//! with a count-prefixed chunk.
+//!
+//! Wiring a [`StreamingBeamWriter`] into the pipeline needs `generate_propagated`/
+//! `generate_unpropagated` to expose a batch iterator or callback instead of returning one
+//! complete `ElectronBeamSwarm`; those methods live on `ElectronBeamSwarm` in the `ebeam` module,
+//! which is not part of this snapshot. What's implemented here is the format-independent piece:
+//! the trait itself, the `--buffer-beams` option, and a newline-delimited-JSON writer (the only
+//! format whose framing doesn't depend on a `.fl`/H5Part layout defined in the absent module).
+
+use crate::exit_with_error;
+use clap::ArgMatches;
+use serde::Serialize;
+use std::{fs::File, io, io::Write, path::Path};
+
+/// Writes a beam swarm to disk in batches of bounded size, so peak memory is independent of the
+/// total number of beams.
+pub trait StreamingBeamWriter<B> {
+    /// Appends one batch of newly-completed beams to the output.
+    fn write_batch(&mut self, beams: &[B]) -> io::Result<()>;
+
+    /// Finalizes the output after the last batch has been written.
+    fn finish(self) -> io::Result<()>;
+}
+
+/// Writes each beam as one newline-delimited JSON (NDJSON) record, so a reader can process the
+/// file beam-by-beam without holding the whole swarm in memory either.
+pub struct NdjsonBeamWriter {
+    file: File,
+}
+
+impl NdjsonBeamWriter {
+    pub fn create<P: AsRef<Path>>(output_file_path: P) -> io::Result<Self> {
+        Ok(Self {
+            file: File::create(output_file_path)?,
+        })
+    }
+}
+
+impl<B: Serialize> StreamingBeamWriter<B> for NdjsonBeamWriter {
+    fn write_batch(&mut self, beams: &[B]) -> io::Result<()> {
+        for beam in beams {
+            serde_json::to_writer(&mut self.file, beam)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+            self.file.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reads the `--buffer-beams` option, exiting with an error if it is not a positive integer.
+pub fn parse_buffer_beams_from_arguments(arguments: &ArgMatches) -> usize {
+    let value_string = arguments
+        .value_of("buffer-beams")
+        .expect("No value for required argument");
+    match value_string.parse::<usize>() {
+        Ok(0) | Err(_) => exit_with_error!(
+            "Error: Invalid value for buffer-beams: {}\n\
+             Must be a positive integer",
+            value_string
+        ),
+        Ok(buffer_beams) => buffer_beams,
+    }
+}
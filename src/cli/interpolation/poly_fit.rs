@@ -33,7 +33,8 @@ pub fn add_poly_fit_interpolator_options_to_subcommand<'a, 'b>(app: App<'a, 'b>)
             )
             .next_line_help(true)
             .takes_value(true)
-            .default_value("0.3"),
+            .default_value("0.3")
+            .validator(cli::validators::in_closed_range(0.0, 1.0)),
     )
 }
 
@@ -52,3 +53,34 @@ pub fn construct_poly_fit_interpolator_config_from_options(
         variation_threshold_for_linear,
     }
 }
+
+/// Like [`construct_poly_fit_interpolator_config_from_options`], but consults `global_config` (a
+/// parsed `--config` document) as a fallback layer below an explicit flag for both options,
+/// nested under `subcommand_path` (e.g. `&["snapshot", "poly_fit_interpolator"]`). Not yet called
+/// from anywhere: the callers of the plain variant (`cli/ebeam/simulate.rs`,
+/// `cli/snapshot/corks.rs`) would need a `GlobalConfig` and matching subcommand path threaded down
+/// to them from `cli::run`, which only loads one today; see `cli::config_file`'s module
+/// documentation for why that wiring isn't done here.
+#[allow(dead_code)]
+pub fn construct_poly_fit_interpolator_config_from_options_with_config(
+    arguments: &ArgMatches,
+    global_config: Option<&cli::config_file::GlobalConfig>,
+    subcommand_path: &[&str],
+) -> PolyFitInterpolatorConfig {
+    let order = cli::get_value_from_required_parseable_argument_with_config(
+        arguments,
+        global_config,
+        subcommand_path,
+        "interpolation-order",
+    );
+    let variation_threshold_for_linear = cli::get_value_from_required_parseable_argument_with_config(
+        arguments,
+        global_config,
+        subcommand_path,
+        "variation-threshold-for-linear-interpolation",
+    );
+    PolyFitInterpolatorConfig {
+        order,
+        variation_threshold_for_linear,
+    }
+}
@@ -3,6 +3,7 @@
 mod regular;
 
 use crate::grid::Grid3;
+use crate::io::compression::CompressionMode;
 use crate::io::mesh;
 use crate::io::snapshot::fdt;
 use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
@@ -18,6 +19,15 @@ pub fn create_create_mesh_subcommand<'a, 'b>() -> App<'a, 'b> {
                 .required(true)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("compression")
+                .long("compression")
+                .value_name("MODE")
+                .help("Compression to apply to the output mesh file")
+                .takes_value(true)
+                .possible_values(&["none", "zstd", "lz4"])
+                .default_value("zstd"),
+        )
         .subcommand(regular::create_regular_mesh_subcommand())
 }
 
@@ -35,6 +45,16 @@ fn write_mesh_file<G: Grid3<fdt>>(root_arguments: &ArgMatches, grid: G) {
         .value_of("OUTPUT_PATH")
         .expect("No value for required argument.");
 
-    mesh::write_mesh_file_from_grid(&grid, output_path)
+    let compression = match root_arguments
+        .value_of("compression")
+        .expect("No value for argument with default")
+    {
+        "none" => CompressionMode::None,
+        "zstd" => CompressionMode::default(),
+        "lz4" => CompressionMode::Lz4,
+        invalid => panic!("Invalid compression mode {}", invalid),
+    };
+
+    mesh::write_mesh_file_from_grid(&grid, output_path, compression)
         .unwrap_or_else(|err| panic!("Could not write mesh file: {}", err));
 }
\ No newline at end of file
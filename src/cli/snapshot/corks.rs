@@ -15,7 +15,10 @@ use crate::{
         },
         utils as cli_utils,
     },
-    corks::{ConstantCorkAdvector, CorkAdvector, CorkSet, CorkStepper, HeunCorkStepper},
+    corks::{
+        ConstantCorkAdvector, CorkAdvector, CorkSet, CorkStepper, CorkStepperConfig,
+        CorkStepperType, HeunCorkStepper, RKF45CorkStepper,
+    },
     grid::Grid3,
     interpolation::{
         poly_fit::{PolyFitInterpolator3, PolyFitInterpolatorConfig},
@@ -29,7 +32,7 @@ use crate::{
 };
 use clap::{Arg, ArgMatches, Command};
 use std::{
-    fmt,
+    fmt, fs,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -110,7 +113,81 @@ pub fn create_corks_subcommand(parent_command_name: &'static str) -> Command<'st
                 .help("Print status messages while tracing corks"),
         );
 
-    add_subcommand_combinations!(command, command_name, true; poly_fit_interpolator, (slice_seeder, volume_seeder, manual_seeder))
+    add_subcommand_combinations!(command, command_name, true; poly_fit_interpolator, cork_stepper, (slice_seeder, volume_seeder, manual_seeder))
+}
+
+/// Builds a representation of the `cork_stepper` command line subcommand.
+fn create_cork_stepper_subcommand() -> Command<'static> {
+    Command::new("cork_stepper")
+        .about("Use the given scheme for stepping corks forward in time")
+        .arg(
+            Arg::new("stepping-scheme")
+                .long("stepping-scheme")
+                .value_name("NAME")
+                .help("Which stepping scheme to use for advecting corks")
+                .takes_value(true)
+                .possible_values(["heun", "rkf45"])
+                .default_value("heun"),
+        )
+        .arg(
+            Arg::new("rel-tolerance")
+                .long("rel-tolerance")
+                .value_name("VALUE")
+                .help("Relative error tolerance for the adaptive RKF45 stepper")
+                .takes_value(true)
+                .default_value("1e-6"),
+        )
+        .arg(
+            Arg::new("abs-tolerance")
+                .long("abs-tolerance")
+                .value_name("VALUE")
+                .help("Absolute error tolerance for the adaptive RKF45 stepper")
+                .takes_value(true)
+                .default_value("1e-6"),
+        )
+        .arg(
+            Arg::new("min-step")
+                .long("min-step")
+                .value_name("VALUE")
+                .help("Smallest step size the adaptive RKF45 stepper is allowed to take")
+                .takes_value(true)
+                .default_value("1e-3"),
+        )
+        .arg(
+            Arg::new("max-step")
+                .long("max-step")
+                .value_name("VALUE")
+                .help("Largest step size the adaptive RKF45 stepper is allowed to take")
+                .takes_value(true)
+                .default_value("1.0"),
+        )
+}
+
+/// Determines cork stepper parameters based on provided options.
+fn construct_cork_stepper_config_from_options(
+    arguments: &ArgMatches,
+) -> (CorkStepperType, CorkStepperConfig) {
+    let stepper_type = match arguments.value_of("stepping-scheme").expect("No default") {
+        "heun" => CorkStepperType::Heun,
+        "rkf45" => CorkStepperType::RKF45,
+        invalid => exit_with_error!("Error: Invalid stepping scheme {}", invalid),
+    };
+    let rel_tolerance =
+        cli_utils::get_value_from_required_parseable_argument(arguments, "rel-tolerance");
+    let abs_tolerance =
+        cli_utils::get_value_from_required_parseable_argument(arguments, "abs-tolerance");
+    let min_step = cli_utils::get_value_from_required_parseable_argument(arguments, "min-step");
+    let max_step = cli_utils::get_value_from_required_parseable_argument(arguments, "max-step");
+    (
+        stepper_type,
+        CorkStepperConfig {
+            rel_tolerance,
+            abs_tolerance,
+            min_step,
+            max_step,
+            ..CorkStepperConfig::default()
+        },
+    )
 }
 
 /// Runs the actions for the `snapshot-corks` subcommand using the given arguments.
@@ -157,7 +234,7 @@ fn run_with_selected_interpolator<G, P>(
 
     let interpolator = PolyFitInterpolator3::new(interpolator_config);
 
-    run_tracing(
+    run_with_selected_stepper(
         root_arguments,
         interpolator_arguments,
         snapshot,
@@ -168,12 +245,49 @@ fn run_with_selected_interpolator<G, P>(
     );
 }
 
+fn run_with_selected_stepper<G, P, I>(
+    root_arguments: &ArgMatches,
+    arguments: &ArgMatches,
+    snapshot: &mut SnapshotCacher3<G, P>,
+    snap_num_in_range: &Option<SnapNumInRange>,
+    interpolator: I,
+    protected_file_types: &[&str],
+    corks_state: &mut Option<CorksState>,
+) where
+    G: Grid3<fdt>,
+    P: SnapshotProvider3<G> + Sync,
+    I: Interpolator3,
+{
+    let (stepper_type, stepper_config, stepper_arguments) =
+        if let Some(stepper_arguments) = arguments.subcommand_matches("cork_stepper") {
+            let (stepper_type, stepper_config) =
+                construct_cork_stepper_config_from_options(stepper_arguments);
+            (stepper_type, stepper_config, stepper_arguments)
+        } else {
+            (CorkStepperType::Heun, CorkStepperConfig::default(), arguments)
+        };
+
+    run_tracing(
+        root_arguments,
+        stepper_arguments,
+        snapshot,
+        snap_num_in_range,
+        interpolator,
+        stepper_type,
+        stepper_config,
+        protected_file_types,
+        corks_state,
+    );
+}
+
 fn run_tracing<G, P, I>(
     root_arguments: &ArgMatches,
     arguments: &ArgMatches,
     snapshot: &mut SnapshotCacher3<G, P>,
     snap_num_in_range: &Option<SnapNumInRange>,
     interpolator: I,
+    stepper_type: CorkStepperType,
+    stepper_config: CorkStepperConfig,
     protected_file_types: &[&str],
     corks_state: &mut Option<CorksState>,
 ) where
@@ -187,11 +301,13 @@ fn run_tracing<G, P, I>(
             arguments,
             snapshot,
             interpolator,
+            stepper_type,
+            &stepper_config,
             corks_state,
         );
     } else {
         let corks = corks_state.as_mut().expect("Corks state not initialized");
-        advect_with_selected_advector(snapshot, interpolator, corks);
+        advect_with_selected_advector(snapshot, interpolator, corks, stepper_type, stepper_config);
     }
     write_output(
         root_arguments,
@@ -210,26 +326,66 @@ fn initialize_with_selected_seeder<G, P, I>(
     arguments: &ArgMatches,
     snapshot: &mut SnapshotCacher3<G, P>,
     interpolator: I,
+    stepper_type: CorkStepperType,
+    stepper_config: &CorkStepperConfig,
     corks_state: &mut Option<CorksState>,
 ) where
     G: Grid3<fdt>,
     P: SnapshotProvider3<G> + Sync,
     I: Interpolator3,
 {
+    let initial_step_size = initial_step_size_for_stepper(stepper_type, stepper_config);
+
     if let Some(seeder_arguments) = arguments.subcommand_matches("slice_seeder") {
         let seeder = create_slice_seeder_from_arguments(seeder_arguments, snapshot, &interpolator);
-        initialize_corks(root_arguments, snapshot, interpolator, seeder, corks_state);
+        initialize_corks(
+            root_arguments,
+            snapshot,
+            interpolator,
+            seeder,
+            initial_step_size,
+            corks_state,
+        );
     } else if let Some(seeder_arguments) = arguments.subcommand_matches("volume_seeder") {
         let seeder = create_volume_seeder_from_arguments(seeder_arguments, snapshot, &interpolator);
-        initialize_corks(root_arguments, snapshot, interpolator, seeder, corks_state);
+        initialize_corks(
+            root_arguments,
+            snapshot,
+            interpolator,
+            seeder,
+            initial_step_size,
+            corks_state,
+        );
     } else if let Some(seeder_arguments) = arguments.subcommand_matches("manual_seeder") {
         let seeder = create_manual_seeder_from_arguments(seeder_arguments);
-        initialize_corks(root_arguments, snapshot, interpolator, seeder, corks_state);
+        initialize_corks(
+            root_arguments,
+            snapshot,
+            interpolator,
+            seeder,
+            initial_step_size,
+            corks_state,
+        );
     } else {
         exit_with_error!("Error: No seeder specified")
     };
 }
 
+/// Picks the step size every cork is bootstrapped with, from the stepping scheme's configured
+/// bounds: [`CorkStepperConfig::min_step`] for the adaptive RKF45 stepper (so the first step
+/// attempt is conservative; [`RKF45CorkStepper::step`] then grows or shrinks it from the error
+/// estimate, within `[min_step, max_step]`), and [`CorkStepperConfig::max_step`] for the
+/// non-adaptive Heun stepper (which never revises the step size it's given, so it needs to start
+/// at the largest step the command line allows rather than the smallest). Ideally this would
+/// also be clamped to the time spanned by the snapshot sequence the corks are traced through, but
+/// that cadence isn't available to this CLI layer.
+fn initial_step_size_for_stepper(stepper_type: CorkStepperType, stepper_config: &CorkStepperConfig) -> fdt {
+    match stepper_type {
+        CorkStepperType::Heun => stepper_config.max_step,
+        CorkStepperType::RKF45 => stepper_config.min_step,
+    }
+}
+
 fn obtain_sampled_quantity_names(
     root_arguments: &ArgMatches,
 ) -> (Vec<String>, Vec<String>, Vec<String>) {
@@ -280,6 +436,7 @@ fn initialize_corks<G, P, I, Sd>(
     snapshot: &mut SnapshotCacher3<G, P>,
     interpolator: I,
     seeder: Sd,
+    initial_step_size: fdt,
     corks_state: &mut Option<CorksState>,
 ) where
     G: Grid3<fdt>,
@@ -298,6 +455,7 @@ fn initialize_corks<G, P, I, Sd>(
             scalar_quantity_names,
             vector_quantity_names,
             vector_magnitude_names,
+            initial_step_size,
             root_arguments.is_present("verbose").into(),
         ),
         "Error: Could not initialize corks: {}"
@@ -309,6 +467,8 @@ fn advect_with_selected_advector<G, P, I>(
     snapshot: &mut SnapshotCacher3<G, P>,
     interpolator: I,
     corks: &mut CorkSet,
+    stepper_type: CorkStepperType,
+    stepper_config: CorkStepperConfig,
 ) where
     G: Grid3<fdt>,
     P: SnapshotProvider3<G> + Sync,
@@ -316,23 +476,32 @@ fn advect_with_selected_advector<G, P, I>(
 {
     let advector = ConstantCorkAdvector;
 
-    advect_with_selected_stepper(snapshot, interpolator, advector, corks);
+    advect_with_stepper_type(snapshot, interpolator, advector, corks, stepper_type, stepper_config);
 }
 
-fn advect_with_selected_stepper<G, P, I, A>(
+fn advect_with_stepper_type<G, P, I, A>(
     snapshot: &mut SnapshotCacher3<G, P>,
     interpolator: I,
     advector: A,
     corks: &mut CorkSet,
+    stepper_type: CorkStepperType,
+    stepper_config: CorkStepperConfig,
 ) where
     G: Grid3<fdt>,
     P: SnapshotProvider3<G> + Sync,
     I: Interpolator3,
     A: CorkAdvector,
 {
-    let stepper = HeunCorkStepper;
-
-    advect_corks(snapshot, interpolator, advector, stepper, corks);
+    match stepper_type {
+        CorkStepperType::Heun => {
+            let stepper = HeunCorkStepper;
+            advect_corks(snapshot, interpolator, advector, stepper, corks);
+        }
+        CorkStepperType::RKF45 => {
+            let stepper = RKF45CorkStepper::new(stepper_config);
+            advect_corks(snapshot, interpolator, advector, stepper, corks);
+        }
+    }
 }
 
 fn advect_corks<G, P, I, A, St>(
@@ -392,6 +561,15 @@ fn write_output(
 
         let overwrite_mode = cli_utils::overwrite_mode_from_arguments(root_arguments);
 
+        if let Some(output_dir) = output_file_path.parent() {
+            if !output_dir.as_os_str().is_empty() {
+                exit_on_error!(
+                    fs::DirBuilder::new().recursive(true).create(output_dir),
+                    "Error: Could not create output directory: {}"
+                );
+            }
+        }
+
         let atomic_output_path = exit_on_error!(
             AtomicOutputPath::new(output_file_path),
             "Error: Could not create temporary output file: {}"
@@ -420,13 +598,16 @@ fn write_output(
 
         exit_on_error!(
             match output_type {
-                OutputType::Cork => unimplemented!(),
+                OutputType::Cork => corks.save_as_binary(atomic_output_path.temporary_path()),
                 #[cfg(feature = "pickle")]
                 OutputType::Pickle => corks.save_as_pickle(atomic_output_path.temporary_path()),
                 #[cfg(feature = "json")]
                 OutputType::JSON => corks.save_as_json(atomic_output_path.temporary_path()),
                 #[cfg(feature = "hdf5")]
-                OutputType::H5Part => unimplemented!(),
+                OutputType::H5Part => corks.save_as_h5part(
+                    atomic_output_path.temporary_path(),
+                    root_arguments.is_present("drop-h5part-id"),
+                ),
             },
             "Error: Could not save output data: {}"
         );
@@ -0,0 +1,55 @@
+//! Command line interface for resampling a snapshot using kernel convolution.
+
+use crate::{io::snapshot::fdt, resampling::ResamplingKernel};
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+/// Builds a representation of the `kernel_convolution` command line subcommand.
+pub fn create_kernel_convolution_subcommand<'a, 'b>() -> App<'a, 'b> {
+    SubCommand::with_name("kernel_convolution")
+        .about("Use kernel-convolution resampling")
+        .long_about(
+            "Use kernel-convolution resampling.\n\
+             Each new value is found by convolving the field with a smoothing kernel\n\
+             separably along each axis, with the kernel footprint scaled to the\n\
+             output-to-input cell size ratio. This gives properly anti-aliased\n\
+             downsampling, in contrast to the more noisy result of direct sampling.",
+        )
+        .arg(
+            Arg::with_name("kernel")
+                .long("kernel")
+                .value_name("NAME")
+                .long_help("Smoothing kernel to convolve the field with")
+                .takes_value(true)
+                .possible_values(&["gaussian", "linear", "ball"])
+                .default_value("gaussian"),
+        )
+        .arg(
+            Arg::with_name("sigma-radius")
+                .long("sigma-radius")
+                .value_name("VALUE")
+                .long_help("Truncation radius of the Gaussian kernel, in standard deviations")
+                .takes_value(true)
+                .default_value("3.0"),
+        )
+}
+
+/// Determines the kernel-convolution resampling kernel to use based on the
+/// provided arguments.
+pub fn construct_resampling_kernel_from_options(arguments: &ArgMatches) -> ResamplingKernel<fdt> {
+    match arguments
+        .value_of("kernel")
+        .expect("No value for argument with default")
+    {
+        "gaussian" => {
+            let sigma_radius = arguments
+                .value_of("sigma-radius")
+                .expect("No value for argument with default")
+                .parse()
+                .unwrap_or_else(|err| panic!("Could not parse value of sigma-radius: {}", err));
+            ResamplingKernel::Gaussian { sigma_radius }
+        }
+        "linear" => ResamplingKernel::Linear,
+        "ball" => ResamplingKernel::Ball,
+        invalid => panic!("Invalid kernel {}", invalid),
+    }
+}
@@ -1,8 +1,15 @@
 //! Command line interface for Runge-Kutta-Fehlberg steppers.
 
 use crate::cli;
-use crate::tracing::stepping::rkf::{RKFStepperConfig, RKFStepperType};
+use crate::exit_with_error;
+use crate::tracing::stepping::rkf::{
+    config_file::RKFStepperConfigFileSpec,
+    step_controller::{StepControllerConfig, StepControllerMode},
+    step_ramping::StepRampingConfig,
+    RKFStepperConfig, RKFStepperType,
+};
 use clap::{App, Arg, ArgMatches, SubCommand};
+use std::str::FromStr;
 
 /// Creates a subcommand for using a Runge-Kutta-Fehlberg stepper.
 pub fn create_rkf_stepper_subcommand<'a, 'b>() -> App<'a, 'b> {
@@ -103,14 +110,123 @@ pub fn add_rkf_stepper_options_to_subcommand<'a, 'b>(app: App<'a, 'b>) -> App<'a
             .default_value("3"),
     )
     .arg(
-        Arg::with_name("disable-pi-control")
-            .long("disable-pi-control")
-            .help("Disable Proportional Integral (PI) control used for stabilizing the stepping"),
+        Arg::with_name("step-controller")
+            .long("step-controller")
+            .value_name("NAME")
+            .long_help(
+                "Step-size feedback algorithm to use\n\
+                   standard:   scale = safety * err^(-1/(p+1)), no dependence on step history\n\
+                   pi:         scale = safety * err^(-k_I) * err_prev^(k_P)\n\
+                   pid:        the pi formula, additionally multiplied by err_prev2^(-k_D)\n\
+                   gustafsson: the pi formula, additionally multiplied by the predictive\n\
+                               factor h_n/h_{n-1}",
+            )
+            .next_line_help(true)
+            .takes_value(true)
+            .possible_values(&["standard", "pi", "pid", "gustafsson"])
+            .default_value("pi"),
+    )
+    .arg(
+        Arg::with_name("step-controller-integral-gain")
+            .long("step-controller-integral-gain")
+            .value_name("VALUE")
+            .long_help(
+                "Integral gain k_I for the pi, pid and gustafsson step controllers\n\
+                 [default: 0.7/(p+1), p being the stepping scheme order]",
+            )
+            .next_line_help(true)
+            .takes_value(true),
+    )
+    .arg(
+        Arg::with_name("step-controller-proportional-gain")
+            .long("step-controller-proportional-gain")
+            .value_name("VALUE")
+            .long_help(
+                "Proportional gain k_P for the pi, pid and gustafsson step controllers\n\
+                 [default: 0.4/(p+1), p being the stepping scheme order]",
+            )
+            .next_line_help(true)
+            .takes_value(true),
+    )
+    .arg(
+        Arg::with_name("step-controller-derivative-gain")
+            .long("step-controller-derivative-gain")
+            .value_name("VALUE")
+            .long_help("Derivative gain k_D for the pid step controller")
+            .next_line_help(true)
+            .takes_value(true)
+            .default_value("0.1"),
+    )
+    .arg(
+        Arg::with_name("step-controller-deadband-low")
+            .long("step-controller-deadband-low")
+            .value_name("VALUE")
+            .long_help(
+                "Lower bound of the step scale deadband: scale factors inside\n\
+                 [deadband-low, deadband-high] leave the step size unchanged",
+            )
+            .next_line_help(true)
+            .takes_value(true)
+            .default_value("1.0"),
+    )
+    .arg(
+        Arg::with_name("step-controller-deadband-high")
+            .long("step-controller-deadband-high")
+            .value_name("VALUE")
+            .long_help(
+                "Upper bound of the step scale deadband: scale factors inside\n\
+                 [deadband-low, deadband-high] leave the step size unchanged",
+            )
+            .next_line_help(true)
+            .takes_value(true)
+            .default_value("1.2"),
+    )
+    .arg(
+        Arg::with_name("step-ramping-factor")
+            .long("step-ramping-factor")
+            .value_name("VALUE")
+            .long_help(
+                "Largest allowed ratio of the step size to the previous one during the first\n\
+                 `step-ramping-steps` accepted steps of a trace, overriding `max-step-scale`\n\
+                 while it applies. Guards against an overly large, poorly-justified first step\n\
+                 from the seed point, before the error history has stabilized.",
+            )
+            .next_line_help(true)
+            .takes_value(true)
+            .default_value("2.0"),
+    )
+    .arg(
+        Arg::with_name("step-ramping-steps")
+            .long("step-ramping-steps")
+            .value_name("NUMBER")
+            .long_help("Number of accepted steps, from the seed point, for which step-ramping-factor applies")
+            .next_line_help(true)
+            .takes_value(true)
+            .default_value("5"),
+    )
+    .arg(
+        Arg::with_name("stepper-config-file")
+            .long("stepper-config-file")
+            .value_name("PATH")
+            .long_help(
+                "Path to a YAML file with defaults for the stepper parameters above, using the\n\
+                 same names with underscores (e.g. `absolute_tolerance`, `stepping_scheme`).\n\
+                 A flag given explicitly on the command line overrides the corresponding entry\n\
+                 in this file.",
+            )
+            .next_line_help(true)
+            .takes_value(true),
     )
     .arg(
         Arg::with_name("stepping-scheme")
             .long("stepping-scheme")
             .value_name("NAME")
+            // A Fehlberg 7(8) scheme (`tracing::stepping::rkf::rkf78::RKF78Stepper3`) is
+            // implemented for smooth-field long-distance tracing, but cannot be exposed as a
+            // "rkf78" possible value here yet: doing so requires a new `RKFStepperType::RKF78`
+            // variant, and that enum is defined in `rkf/mod.rs`, which this snapshot does not
+            // include. Add the variant and a `"rkf78" => RKFStepperType::RKF78` arm below once
+            // that file is available.
             .long_help("Which Runge-Kutta-Fehlberg stepping scheme to use")
             .next_line_help(true)
             .takes_value(true)
@@ -119,40 +235,169 @@ pub fn add_rkf_stepper_options_to_subcommand<'a, 'b>(app: App<'a, 'b>) -> App<'a
     )
 }
 
+/// Reads the optional `--stepper-config-file` YAML file, if given, exiting with an error
+/// message (naming the offending key/line) if it cannot be read or parsed.
+fn read_stepper_config_file(arguments: &ArgMatches) -> RKFStepperConfigFileSpec {
+    match arguments.value_of("stepper-config-file") {
+        Some(path) => match RKFStepperConfigFileSpec::from_yaml_file(path) {
+            Ok(spec) => spec,
+            Err(message) => exit_with_error!("{}", message),
+        },
+        None => RKFStepperConfigFileSpec::default(),
+    }
+}
+
+/// Resolves a stepper parameter, preferring an explicitly given CLI flag, then the stepper
+/// config file, then the flag's hardcoded default.
+fn resolve_stepper_argument<T>(
+    arguments: &ArgMatches,
+    argument_name: &str,
+    file_value: Option<T>,
+) -> T
+where
+    T: FromStr,
+    <T as FromStr>::Err: std::fmt::Display,
+{
+    if arguments.occurrences_of(argument_name) > 0 {
+        cli::get_value_from_required_parseable_argument(arguments, argument_name)
+    } else if let Some(value) = file_value {
+        value
+    } else {
+        cli::get_value_from_required_parseable_argument(arguments, argument_name)
+    }
+}
+
 /// Determines Runge-Kutta-Fehlberg stepper parameters based on
 /// provided options.
 pub fn construct_rkf_stepper_config_from_options(
     arguments: &ArgMatches,
 ) -> (RKFStepperType, RKFStepperConfig) {
-    let dense_step_length =
-        cli::get_value_from_required_parseable_argument(arguments, "dense-step-length");
-    let max_step_attempts =
-        cli::get_value_from_required_parseable_argument(arguments, "max-step-attempts");
-    let absolute_tolerance =
-        cli::get_value_from_required_parseable_argument(arguments, "stepping-absolute-tolerance");
-    let relative_tolerance =
-        cli::get_value_from_required_parseable_argument(arguments, "stepping-relative-tolerance");
-    let safety_factor =
-        cli::get_value_from_required_parseable_argument(arguments, "stepping-safety-factor");
+    let config_file = read_stepper_config_file(arguments);
+
+    let dense_step_length = resolve_stepper_argument(
+        arguments,
+        "dense-step-length",
+        config_file.dense_step_length,
+    );
+    let max_step_attempts = resolve_stepper_argument(
+        arguments,
+        "max-step-attempts",
+        config_file.max_step_attempts,
+    );
+    let absolute_tolerance = resolve_stepper_argument(
+        arguments,
+        "stepping-absolute-tolerance",
+        config_file.absolute_tolerance,
+    );
+    let relative_tolerance = resolve_stepper_argument(
+        arguments,
+        "stepping-relative-tolerance",
+        config_file.relative_tolerance,
+    );
+    let safety_factor = resolve_stepper_argument(
+        arguments,
+        "stepping-safety-factor",
+        config_file.safety_factor,
+    );
     let min_step_scale =
-        cli::get_value_from_required_parseable_argument(arguments, "min-step-scale");
+        resolve_stepper_argument(arguments, "min-step-scale", config_file.min_step_scale);
     let max_step_scale =
-        cli::get_value_from_required_parseable_argument(arguments, "max-step-scale");
-    let initial_error =
-        cli::get_value_from_required_parseable_argument(arguments, "stepping-initial-error");
-    let initial_step_length =
-        cli::get_value_from_required_parseable_argument(arguments, "initial-step-length");
-    let sudden_reversals_for_sink =
-        cli::get_value_from_required_parseable_argument(arguments, "sudden-reversals-for-sink");
-    let use_pi_control = !arguments.is_present("disable-pi-control");
+        resolve_stepper_argument(arguments, "max-step-scale", config_file.max_step_scale);
+    let initial_error = resolve_stepper_argument(
+        arguments,
+        "stepping-initial-error",
+        config_file.initial_error,
+    );
+    let initial_step_length = resolve_stepper_argument(
+        arguments,
+        "initial-step-length",
+        config_file.initial_step_length,
+    );
+    let sudden_reversals_for_sink = resolve_stepper_argument(
+        arguments,
+        "sudden-reversals-for-sink",
+        config_file.sudden_reversals_for_sink,
+    );
+
+    let stepper_type = if arguments.occurrences_of("stepping-scheme") > 0 {
+        cli::get_value_from_required_constrained_argument(
+            arguments,
+            "stepping-scheme",
+            &["rkf23", "rkf45"],
+            &[RKFStepperType::RKF23, RKFStepperType::RKF45],
+        )
+    } else {
+        match config_file.stepping_scheme.as_deref() {
+            Some("rkf23") => RKFStepperType::RKF23,
+            Some("rkf45") => RKFStepperType::RKF45,
+            Some(invalid) => {
+                exit_with_error!("Error: Invalid value for stepping_scheme: {}", invalid)
+            }
+            None => cli::get_value_from_required_constrained_argument(
+                arguments,
+                "stepping-scheme",
+                &["rkf23", "rkf45"],
+                &[RKFStepperType::RKF23, RKFStepperType::RKF45],
+            ),
+        }
+    };
 
-    let stepper_type = cli::get_value_from_required_constrained_argument(
+    // The default integral/proportional gains depend on the order of the chosen scheme.
+    let order = match stepper_type {
+        RKFStepperType::RKF23 => 3,
+        RKFStepperType::RKF45 => 5,
+    };
+
+    let step_controller_mode = match arguments
+        .value_of("step-controller")
+        .expect("No value for required argument")
+    {
+        "standard" => StepControllerMode::Standard,
+        "pi" => StepControllerMode::Pi,
+        "pid" => StepControllerMode::Pid,
+        "gustafsson" => StepControllerMode::Gustafsson,
+        invalid => unreachable!("Invalid value for step-controller: {}", invalid),
+    };
+    let integral_gain = arguments
+        .value_of("step-controller-integral-gain")
+        .map(|value_string| cli::parse_value_string("step-controller-integral-gain", value_string))
+        .unwrap_or_else(|| StepControllerConfig::default_integral_gain(order));
+    let proportional_gain = arguments
+        .value_of("step-controller-proportional-gain")
+        .map(|value_string| {
+            cli::parse_value_string("step-controller-proportional-gain", value_string)
+        })
+        .unwrap_or_else(|| StepControllerConfig::default_proportional_gain(order));
+    let derivative_gain = cli::get_value_from_required_parseable_argument(
+        arguments,
+        "step-controller-derivative-gain",
+    );
+    let deadband_lo =
+        cli::get_value_from_required_parseable_argument(arguments, "step-controller-deadband-low");
+    let deadband_hi = cli::get_value_from_required_parseable_argument(
         arguments,
-        "stepping-scheme",
-        &["peaked", "isotropic"],
-        &[RKFStepperType::RKF23, RKFStepperType::RKF45],
+        "step-controller-deadband-high",
     );
 
+    let step_controller = StepControllerConfig {
+        mode: step_controller_mode,
+        integral_gain,
+        proportional_gain,
+        derivative_gain,
+        deadband_lo,
+        deadband_hi,
+    };
+
+    let ramping_factor =
+        cli::get_value_from_required_parseable_argument(arguments, "step-ramping-factor");
+    let ramping_steps =
+        cli::get_value_from_required_parseable_argument(arguments, "step-ramping-steps");
+
+    let step_ramping = StepRampingConfig {
+        ramping_factor,
+        ramping_steps,
+    };
+
     (
         stepper_type,
         RKFStepperConfig {
@@ -166,7 +411,8 @@ pub fn construct_rkf_stepper_config_from_options(
             initial_error,
             initial_step_length,
             sudden_reversals_for_sink,
-            use_pi_control,
+            step_controller,
+            step_ramping,
         },
     )
 }
@@ -0,0 +1,66 @@
+//! Reusable numeric argument validators.
+//!
+//! `Arg::validator` lets clap reject a bad value at parse time, with the offending argument name
+//! already in context, rather than the value only being checked once some `construct_*_config`
+//! function gets around to parsing it (as `variation-threshold-for-linear-interpolation` used to
+//! be). The helpers here build such a validator closure for the numeric bounds that come up
+//! repeatedly across subcommands: [`in_closed_range`] for a `[lo, hi]` bound,
+//! [`positive`] for `> 0`, and [`nonzero`] for `!= 0`.
+
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Builds a validator rejecting any value that doesn't parse as `T` or doesn't lie in the closed
+/// range `[lo, hi]`.
+pub fn in_closed_range<T>(lo: T, hi: T) -> impl Fn(String) -> Result<(), String>
+where
+    T: FromStr + PartialOrd + Display + Copy + 'static,
+{
+    move |value_string: String| {
+        let value: T = value_string
+            .parse()
+            .map_err(|_| format!("'{}' is not a valid number", value_string))?;
+        if value < lo || value > hi {
+            Err(format!(
+                "'{}' is not in the required range [{}, {}]",
+                value_string, lo, hi
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Builds a validator rejecting any value that doesn't parse as `T` or isn't strictly positive.
+pub fn positive<T>() -> impl Fn(String) -> Result<(), String>
+where
+    T: FromStr + PartialOrd + Default + Display + Copy + 'static,
+{
+    move |value_string: String| {
+        let value: T = value_string
+            .parse()
+            .map_err(|_| format!("'{}' is not a valid number", value_string))?;
+        if value > T::default() {
+            Ok(())
+        } else {
+            Err(format!("'{}' is not a positive number", value_string))
+        }
+    }
+}
+
+/// Builds a validator rejecting any value that doesn't parse as `T` or equals zero.
+pub fn nonzero<T>() -> impl Fn(String) -> Result<(), String>
+where
+    T: FromStr + PartialEq + Default + Display + Copy + 'static,
+{
+    move |value_string: String| {
+        let value: T = value_string
+            .parse()
+            .map_err(|_| format!("'{}' is not a valid number", value_string))?;
+        if value == T::default() {
+            Err(format!("'{}' must not be zero", value_string))
+        } else {
+            Ok(())
+        }
+    }
+}
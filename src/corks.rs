@@ -0,0 +1,751 @@
+//! Tracing of corks (passively advected tracer particles) through a velocity field.
+
+use crate::{
+    field::ScalarFieldProvider3,
+    geometry::{Dim3, Point3, Vec3},
+    grid::Grid3,
+    interpolation::Interpolator3,
+    io::{
+        snapshot::{fdt, SnapshotCacher3, SnapshotProvider3},
+        utils, Endianness, Verbose,
+    },
+    seeding::Seeder3,
+};
+use std::{io, mem, path::Path};
+
+#[cfg(feature = "hdf5")]
+use crate::io_result;
+#[cfg(feature = "hdf5")]
+use hdf5_rs as hdf5;
+
+/// Per-cork record of sampled positions and quantities over the steps
+/// where the cork exists.
+#[derive(Clone, Debug)]
+struct Cork {
+    /// Index of the first step at which the cork exists.
+    first_valid_step: usize,
+    /// Index of the last step at which the cork exists, if it has ended.
+    last_valid_step: Option<usize>,
+    /// Position of the cork at each step it exists.
+    positions: Vec<Point3<fdt>>,
+    /// Suggested step size to use for the next advection step.
+    suggested_step_size: fdt,
+    /// Sampled scalar quantity values at each step, one `Vec` per quantity.
+    scalar_values: Vec<Vec<fdt>>,
+    /// Sampled vector quantity values at each step, one `Vec` per quantity.
+    vector_values: Vec<Vec<Vec3<fdt>>>,
+    /// Sampled vector magnitude values at each step, one `Vec` per quantity.
+    vector_magnitude_values: Vec<Vec<fdt>>,
+}
+
+impl Cork {
+    fn new(
+        step: usize,
+        position: Point3<fdt>,
+        initial_step_size: fdt,
+        n_scalar_quantities: usize,
+        n_vector_quantities: usize,
+        n_vector_magnitude_quantities: usize,
+    ) -> Self {
+        Self {
+            first_valid_step: step,
+            last_valid_step: None,
+            positions: vec![position],
+            suggested_step_size: initial_step_size,
+            scalar_values: vec![Vec::new(); n_scalar_quantities],
+            vector_values: vec![Vec::new(); n_vector_quantities],
+            vector_magnitude_values: vec![Vec::new(); n_vector_magnitude_quantities],
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.last_valid_step.is_none()
+    }
+
+    fn number_of_steps(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn terminate(&mut self, step: usize) {
+        self.last_valid_step = Some(step);
+    }
+}
+
+/// A set of corks being advected through the velocity field of a sequence of snapshots.
+#[derive(Clone, Debug)]
+pub struct CorkSet {
+    scalar_quantity_names: Vec<String>,
+    vector_quantity_names: Vec<String>,
+    vector_magnitude_names: Vec<String>,
+    corks: Vec<Cork>,
+    current_step: usize,
+    verbose: Verbose,
+}
+
+impl CorkSet {
+    /// Creates a new set of corks seeded at the positions produced by the given seeder,
+    /// sampling the requested scalar, vector, and vector magnitude quantities.
+    ///
+    /// `initial_step_size` seeds every cork's first advection step (see
+    /// [`CorkStepper::step`]'s `suggested_step_size` parameter). It must be strictly positive:
+    /// a `0.0` bootstrap leaves [`HeunCorkStepper`] stuck returning the same zero-length step
+    /// forever, since it never re-derives a step size on its own. Callers should derive this
+    /// from the stepping scheme's configured step bounds (and, ideally, the cadence of the
+    /// snapshot sequence the corks are traced through, once that is available to the caller).
+    pub fn new<G, P, I, Sd>(
+        seeder: Sd,
+        snapshot: &mut SnapshotCacher3<G, P>,
+        interpolator: &I,
+        scalar_quantity_names: Vec<String>,
+        vector_quantity_names: Vec<String>,
+        vector_magnitude_names: Vec<String>,
+        initial_step_size: fdt,
+        verbose: Verbose,
+    ) -> io::Result<Self>
+    where
+        G: Grid3<fdt>,
+        P: SnapshotProvider3<G> + Sync,
+        I: Interpolator3,
+        Sd: Seeder3,
+    {
+        assert!(
+            initial_step_size > 0.0,
+            "initial_step_size must be strictly positive, or corks using a non-adaptive \
+             stepper will never advect"
+        );
+
+        if verbose.is_yes() {
+            println!("Found {} cork seed positions", seeder.number_of_points());
+        }
+
+        let n_scalar_quantities = scalar_quantity_names.len();
+        let n_vector_quantities = vector_quantity_names.len();
+        let n_vector_magnitude_quantities = vector_magnitude_names.len();
+
+        let corks = seeder
+            .into_iter()
+            .map(|position| {
+                Cork::new(
+                    0,
+                    Point3::from(&position),
+                    initial_step_size,
+                    n_scalar_quantities,
+                    n_vector_quantities,
+                    n_vector_magnitude_quantities,
+                )
+            })
+            .collect();
+
+        let mut corks = Self {
+            scalar_quantity_names,
+            vector_quantity_names,
+            vector_magnitude_names,
+            corks,
+            current_step: 0,
+            verbose,
+        };
+        corks.sample_quantities_at_current_step(snapshot, interpolator)?;
+        Ok(corks)
+    }
+
+    /// Whether the cork set is verbose.
+    pub fn verbose(&self) -> Verbose {
+        self.verbose
+    }
+
+    /// Returns the number of corks in the set, including ones that have been terminated.
+    pub fn number_of_corks(&self) -> usize {
+        self.corks.len()
+    }
+
+    fn sample_quantities_at_current_step<G, P, I>(
+        &mut self,
+        snapshot: &mut SnapshotCacher3<G, P>,
+        interpolator: &I,
+    ) -> io::Result<()>
+    where
+        G: Grid3<fdt>,
+        P: SnapshotProvider3<G> + Sync,
+        I: Interpolator3,
+    {
+        for name in self.scalar_quantity_names.clone() {
+            let field = snapshot
+                .obtain_scalar_field(&name)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+            let quantity_idx = self
+                .scalar_quantity_names
+                .iter()
+                .position(|n| *n == name)
+                .unwrap();
+            for cork in self.corks.iter_mut().filter(|cork| cork.is_valid()) {
+                let position = cork.positions.last().unwrap().clone();
+                let value = interpolator
+                    .interp_scalar_field(field, &position)
+                    .expect_inside();
+                cork.scalar_values[quantity_idx].push(value);
+            }
+        }
+        for name in self.vector_quantity_names.clone() {
+            let field = snapshot
+                .obtain_vector_field(&name)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+            let quantity_idx = self
+                .vector_quantity_names
+                .iter()
+                .position(|n| *n == name)
+                .unwrap();
+            for cork in self.corks.iter_mut().filter(|cork| cork.is_valid()) {
+                let position = cork.positions.last().unwrap().clone();
+                let value = interpolator
+                    .interp_vector_field(field, &position)
+                    .expect_inside();
+                cork.vector_values[quantity_idx].push(Vec3::from(&value));
+            }
+        }
+        for name in self.vector_magnitude_names.clone() {
+            let field = snapshot
+                .obtain_vector_field(&name)
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+            let quantity_idx = self
+                .vector_magnitude_names
+                .iter()
+                .position(|n| *n == name)
+                .unwrap();
+            for cork in self.corks.iter_mut().filter(|cork| cork.is_valid()) {
+                let position = cork.positions.last().unwrap().clone();
+                let value = interpolator
+                    .interp_vector_field(field, &position)
+                    .expect_inside()
+                    .length();
+                cork.vector_magnitude_values[quantity_idx].push(value);
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes the cork set into the native binary format and saves it at the given path.
+    ///
+    /// This is the only output format available without enabling optional features, so it
+    /// acts as the reference serialization that the other formats mirror.
+    pub fn save_as_binary<P: AsRef<Path>>(&self, output_file_path: P) -> io::Result<()> {
+        let mut file = utils::create_file_and_required_directories(output_file_path)?;
+        self.write_as_binary(&mut file)
+    }
+
+    /// Serializes the cork set into the native binary format and writes it to the given writer.
+    ///
+    /// # Format
+    ///
+    /// ```text
+    /// [HEADER]
+    /// magic: [u8; 4]           ("CORK")
+    /// version: u8
+    /// endianness: u8           (0 = little, 1 = big)
+    /// number_of_corks: u64
+    /// number_of_scalar_quantities: u64
+    /// number_of_vector_quantities: u64
+    /// number_of_vector_magnitude_quantities: u64
+    /// names: string with each name followed by a newline
+    /// [BODY, repeated once per cork]
+    /// first_valid_step: u64
+    /// last_valid_step: u64     (u64::MAX if still active)
+    /// number_of_steps: u64
+    /// positions: [fdt; number_of_steps*3]
+    /// scalar_values: [fdt; number_of_scalar_quantities*number_of_steps]
+    /// vector_values: [fdt; number_of_vector_quantities*number_of_steps*3]
+    /// vector_magnitude_values: [fdt; number_of_vector_magnitude_quantities*number_of_steps]
+    /// ```
+    pub fn write_as_binary<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        const MAGIC: &[u8; 4] = b"CORK";
+        const VERSION: u8 = 1;
+        const ENDIANNESS: Endianness = Endianness::Little;
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, ENDIANNESS as u8])?;
+
+        let mut names = Vec::with_capacity(
+            self.scalar_quantity_names.len()
+                + self.vector_quantity_names.len()
+                + self.vector_magnitude_names.len(),
+        );
+        names.extend(self.scalar_quantity_names.iter().cloned());
+        names.extend(self.vector_quantity_names.iter().cloned());
+        names.extend(self.vector_magnitude_names.iter().cloned());
+        let mut names = names.join("\n");
+        names.push('\n');
+
+        let header_u64_size = mem::size_of::<u64>();
+        let mut header_buffer = vec![0_u8; 4 * header_u64_size];
+        let byte_offset = utils::write_into_byte_buffer(
+            &[
+                self.corks.len() as u64,
+                self.scalar_quantity_names.len() as u64,
+                self.vector_quantity_names.len() as u64,
+                self.vector_magnitude_names.len() as u64,
+            ],
+            &mut header_buffer,
+            0,
+            ENDIANNESS,
+        );
+        writer.write_all(&header_buffer[..byte_offset])?;
+        write!(writer, "{}", names)?;
+
+        for cork in &self.corks {
+            let number_of_steps = cork.number_of_steps();
+            let mut record_buffer = vec![
+                0_u8;
+                header_u64_size * 3
+                    + number_of_steps
+                        * 3
+                        * mem::size_of::<fdt>()
+            ];
+
+            let byte_offset = utils::write_into_byte_buffer(
+                &[
+                    cork.first_valid_step as u64,
+                    cork.last_valid_step.map_or(u64::MAX, |step| step as u64),
+                    number_of_steps as u64,
+                ],
+                &mut record_buffer,
+                0,
+                ENDIANNESS,
+            );
+            writer.write_all(&record_buffer[..byte_offset])?;
+
+            let flat_positions: Vec<fdt> = cork
+                .positions
+                .iter()
+                .flat_map(|position| position.clone().into_iter())
+                .collect();
+            let byte_offset =
+                utils::write_into_byte_buffer(&flat_positions, &mut record_buffer, 0, ENDIANNESS);
+            writer.write_all(&record_buffer[..byte_offset])?;
+
+            for values in &cork.scalar_values {
+                let byte_offset =
+                    utils::write_into_byte_buffer(values, &mut record_buffer, 0, ENDIANNESS);
+                writer.write_all(&record_buffer[..byte_offset])?;
+            }
+            for values in &cork.vector_values {
+                let flat_values: Vec<fdt> = values
+                    .iter()
+                    .flat_map(|vector| vector.clone().into_iter())
+                    .collect();
+                let byte_offset =
+                    utils::write_into_byte_buffer(&flat_values, &mut record_buffer, 0, ENDIANNESS);
+                writer.write_all(&record_buffer[..byte_offset])?;
+            }
+            for values in &cork.vector_magnitude_values {
+                let byte_offset =
+                    utils::write_into_byte_buffer(values, &mut record_buffer, 0, ENDIANNESS);
+                writer.write_all(&record_buffer[..byte_offset])?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the cork set into a H5Part file at the given path.
+    ///
+    /// Each recorded step becomes a `Step#N` group holding the `x`, `y`, and `z` coordinates
+    /// of every cork that is active at that step, along with the sampled scalar, vector, and
+    /// vector magnitude quantities. Unless `drop_id` is set, an `id` dataset identifying the
+    /// originating cork is also written to each group.
+    #[cfg(feature = "hdf5")]
+    pub fn save_as_h5part<P: AsRef<Path>>(&self, file_path: P, drop_id: bool) -> io::Result<()> {
+        if self.corks.is_empty() {
+            eprintln!("Warning: No data to write to H5Part file");
+            return Ok(());
+        }
+
+        utils::create_directory_if_missing(&file_path)?;
+        let file = io_result!(hdf5::File::create(file_path))?;
+
+        let number_of_steps = self
+            .corks
+            .iter()
+            .map(|cork| cork.first_valid_step + cork.number_of_steps())
+            .max()
+            .unwrap_or(0);
+
+        for step in 0..number_of_steps {
+            let active_corks: Vec<(usize, &Cork)> = self
+                .corks
+                .iter()
+                .enumerate()
+                .filter(|(_, cork)| {
+                    step >= cork.first_valid_step && step < cork.first_valid_step + cork.number_of_steps()
+                })
+                .collect();
+
+            if active_corks.is_empty() {
+                continue;
+            }
+
+            let group = io_result!(file.create_group(&format!("Step#{}", step)))?;
+
+            let local_index = |cork: &Cork| step - cork.first_valid_step;
+
+            for (dim, label) in [Dim3::X, Dim3::Y, Dim3::Z].iter().zip(["x", "y", "z"]) {
+                let values: Vec<fdt> = active_corks
+                    .iter()
+                    .map(|(_, cork)| cork.positions[local_index(cork)][*dim])
+                    .collect();
+                io_result!(group.new_dataset_builder().with_data(&values).create(label))?;
+            }
+
+            for (quantity_idx, name) in self.scalar_quantity_names.iter().enumerate() {
+                let values: Vec<fdt> = active_corks
+                    .iter()
+                    .map(|(_, cork)| cork.scalar_values[quantity_idx][local_index(cork)])
+                    .collect();
+                let name = if name == "r" { "rho" } else { name }; // `r` is reserved for radial distance
+                io_result!(group.new_dataset_builder().with_data(&values).create(name))?;
+            }
+
+            for (quantity_idx, name) in self.vector_quantity_names.iter().enumerate() {
+                for (dim, suffix) in [Dim3::X, Dim3::Y, Dim3::Z].iter().zip(["x", "y", "z"]) {
+                    let values: Vec<fdt> = active_corks
+                        .iter()
+                        .map(|(_, cork)| cork.vector_values[quantity_idx][local_index(cork)][*dim])
+                        .collect();
+                    io_result!(group
+                        .new_dataset_builder()
+                        .with_data(&values)
+                        .create(&format!("{}_{}", name, suffix)))?;
+                }
+            }
+
+            for (quantity_idx, name) in self.vector_magnitude_names.iter().enumerate() {
+                let values: Vec<fdt> = active_corks
+                    .iter()
+                    .map(|(_, cork)| cork.vector_magnitude_values[quantity_idx][local_index(cork)])
+                    .collect();
+                io_result!(group.new_dataset_builder().with_data(&values).create(name))?;
+            }
+
+            if !drop_id {
+                let ids: Vec<u64> = active_corks.iter().map(|(idx, _)| *idx as u64).collect();
+                io_result!(group.new_dataset_builder().with_data(&ids).create("id"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Serializes the cork set into JSON format and saves it at the given path.
+    #[cfg(feature = "json")]
+    pub fn save_as_json<P: AsRef<Path>>(&self, output_file_path: P) -> io::Result<()> {
+        utils::save_data_as_json(output_file_path, &self)
+    }
+
+    /// Serializes the cork set into pickle format and saves it at the given path.
+    #[cfg(feature = "pickle")]
+    pub fn save_as_pickle<P: AsRef<Path>>(&self, output_file_path: P) -> io::Result<()> {
+        utils::save_data_as_pickle(output_file_path, &self)
+    }
+}
+
+/// Defines the properties of an advector that moves corks according to a velocity field.
+pub trait CorkAdvector {
+    /// Advects every still-active cork in the set one step using the given stepper.
+    fn advect_corks<G, P, I, St>(
+        &self,
+        corks: &mut CorkSet,
+        snapshot: &mut SnapshotCacher3<G, P>,
+        interpolator: &I,
+        stepper: &St,
+    ) -> io::Result<()>
+    where
+        G: Grid3<fdt>,
+        P: SnapshotProvider3<G> + Sync,
+        I: Interpolator3,
+        St: CorkStepper;
+}
+
+/// Advector that keeps the velocity field fixed for the whole step,
+/// evaluating it only once at the start of the step.
+#[derive(Clone, Copy, Debug)]
+pub struct ConstantCorkAdvector;
+
+impl CorkAdvector for ConstantCorkAdvector {
+    fn advect_corks<G, P, I, St>(
+        &self,
+        corks: &mut CorkSet,
+        snapshot: &mut SnapshotCacher3<G, P>,
+        interpolator: &I,
+        stepper: &St,
+    ) -> io::Result<()>
+    where
+        G: Grid3<fdt>,
+        P: SnapshotProvider3<G> + Sync,
+        I: Interpolator3,
+        St: CorkStepper,
+    {
+        let velocity_field = snapshot
+            .obtain_vector_field("u")
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))?;
+
+        corks.current_step += 1;
+        let step = corks.current_step;
+
+        for cork in corks.corks.iter_mut().filter(|cork| cork.is_valid()) {
+            let position = cork.positions.last().unwrap().clone();
+            match stepper.step(velocity_field, interpolator, &position, cork.suggested_step_size) {
+                Some((next_position, next_step_size)) => {
+                    cork.positions.push(next_position);
+                    cork.suggested_step_size = next_step_size;
+                }
+                None => cork.terminate(step),
+            }
+        }
+
+        corks.sample_quantities_at_current_step(snapshot, interpolator)
+    }
+}
+
+/// Defines the properties of a stepping scheme used to advect a single cork by one step.
+pub trait CorkStepper {
+    /// Advances the given position one step through the velocity field, returning the new
+    /// position and the step size to use for the following step, or `None` if the position
+    /// left the domain or no acceptable step was found.
+    fn step<F, G, I>(
+        &self,
+        velocity_field: &crate::field::VectorField3<F, G>,
+        interpolator: &I,
+        position: &Point3<fdt>,
+        suggested_step_size: fdt,
+    ) -> Option<(Point3<fdt>, fdt)>
+    where
+        F: num::Float,
+        G: Grid3<F>,
+        I: Interpolator3;
+}
+
+/// Cork stepper using Heun's method (second order, non-adaptive).
+#[derive(Clone, Copy, Debug)]
+pub struct HeunCorkStepper;
+
+impl CorkStepper for HeunCorkStepper {
+    fn step<F, G, I>(
+        &self,
+        velocity_field: &crate::field::VectorField3<F, G>,
+        interpolator: &I,
+        position: &Point3<fdt>,
+        suggested_step_size: fdt,
+    ) -> Option<(Point3<fdt>, fdt)>
+    where
+        F: num::Float,
+        G: Grid3<F>,
+        I: Interpolator3,
+    {
+        if !velocity_field.grid().contains_point(position) {
+            return None;
+        }
+        let k1 = Vec3::from(&interpolator.interp_vector_field(velocity_field, position).expect_inside());
+        let predicted_position = position + &k1 * suggested_step_size;
+        let k2 = Vec3::from(
+            &interpolator
+                .interp_vector_field(velocity_field, &predicted_position)
+                .expect_inside(),
+        );
+        let displacement = (&k1 + &k2) * (0.5 * suggested_step_size);
+        Some((position + &displacement, suggested_step_size))
+    }
+}
+
+/// Identifies which cork stepping scheme to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CorkStepperType {
+    Heun,
+    RKF45,
+}
+
+/// Configuration parameters for the adaptive RKF45 cork stepper.
+#[derive(Clone, Debug)]
+pub struct CorkStepperConfig {
+    /// Relative error tolerance used in the scaled error norm.
+    pub rel_tolerance: fdt,
+    /// Absolute error tolerance used in the scaled error norm.
+    pub abs_tolerance: fdt,
+    /// Smallest step size the stepper is allowed to take.
+    pub min_step: fdt,
+    /// Largest step size the stepper is allowed to take.
+    pub max_step: fdt,
+    /// Safety factor applied when computing the new step size.
+    pub safety_factor: fdt,
+    /// Maximum number of rejected attempts before giving up on a step.
+    pub max_step_attempts: u32,
+}
+
+impl Default for CorkStepperConfig {
+    fn default() -> Self {
+        Self {
+            rel_tolerance: 1e-6,
+            abs_tolerance: 1e-6,
+            min_step: 1e-3,
+            max_step: 1.0,
+            safety_factor: 0.9,
+            max_step_attempts: 16,
+        }
+    }
+}
+
+/// Adaptive cork stepper using the embedded Dormand–Prince 5(4) Runge–Kutta pair.
+#[derive(Clone, Debug)]
+pub struct RKF45CorkStepper {
+    config: CorkStepperConfig,
+}
+
+impl RKF45CorkStepper {
+    const C2: fdt = 1.0 / 5.0;
+    const C3: fdt = 3.0 / 10.0;
+    const C4: fdt = 4.0 / 5.0;
+    const C5: fdt = 8.0 / 9.0;
+
+    const A21: fdt = 1.0 / 5.0;
+    const A31: fdt = 3.0 / 40.0;
+    const A32: fdt = 9.0 / 40.0;
+    const A41: fdt = 44.0 / 45.0;
+    const A42: fdt = -56.0 / 15.0;
+    const A43: fdt = 32.0 / 9.0;
+    const A51: fdt = 19372.0 / 6561.0;
+    const A52: fdt = -25360.0 / 2187.0;
+    const A53: fdt = 64448.0 / 6561.0;
+    const A54: fdt = -212.0 / 729.0;
+    const A61: fdt = 9017.0 / 3168.0;
+    const A62: fdt = -355.0 / 33.0;
+    const A63: fdt = 46732.0 / 5247.0;
+    const A64: fdt = 49.0 / 176.0;
+    const A65: fdt = -5103.0 / 18656.0;
+
+    // 5th-order solution weights.
+    const B1: fdt = 35.0 / 384.0;
+    const B3: fdt = 500.0 / 1113.0;
+    const B4: fdt = 125.0 / 192.0;
+    const B5: fdt = -2187.0 / 6784.0;
+    const B6: fdt = 11.0 / 84.0;
+
+    // Differences between the 5th- and embedded 4th-order solution weights.
+    const E1: fdt = 71.0 / 57600.0;
+    const E3: fdt = -71.0 / 16695.0;
+    const E4: fdt = 71.0 / 1920.0;
+    const E5: fdt = -17253.0 / 339200.0;
+    const E6: fdt = 22.0 / 525.0;
+    const E7: fdt = -1.0 / 40.0;
+
+    /// Creates a new RKF45 cork stepper with the given configuration.
+    pub fn new(config: CorkStepperConfig) -> Self {
+        Self { config }
+    }
+
+    fn evaluate<F, G, I>(
+        velocity_field: &crate::field::VectorField3<F, G>,
+        interpolator: &I,
+        position: &Point3<fdt>,
+    ) -> Option<Vec3<fdt>>
+    where
+        F: num::Float,
+        G: Grid3<F>,
+        I: Interpolator3,
+    {
+        if !velocity_field.grid().contains_point(position) {
+            return None;
+        }
+        Some(Vec3::from(
+            &interpolator
+                .interp_vector_field(velocity_field, position)
+                .expect_inside(),
+        ))
+    }
+}
+
+impl CorkStepper for RKF45CorkStepper {
+    fn step<F, G, I>(
+        &self,
+        velocity_field: &crate::field::VectorField3<F, G>,
+        interpolator: &I,
+        position: &Point3<fdt>,
+        suggested_step_size: fdt,
+    ) -> Option<(Point3<fdt>, fdt)>
+    where
+        F: num::Float,
+        G: Grid3<F>,
+        I: Interpolator3,
+    {
+        let mut h = suggested_step_size;
+        let k1 = Self::evaluate(velocity_field, interpolator, position)?;
+
+        for _ in 0..self.config.max_step_attempts {
+            let p2 = position + &k1 * (Self::A21 * h);
+            let k2 = Self::evaluate(velocity_field, interpolator, &p2)?;
+
+            let p3 = position + &(&k1 * Self::A31 + &k2 * Self::A32) * h;
+            let k3 = Self::evaluate(velocity_field, interpolator, &p3)?;
+
+            let p4 = position + &(&k1 * Self::A41 + &k2 * Self::A42 + &k3 * Self::A43) * h;
+            let k4 = Self::evaluate(velocity_field, interpolator, &p4)?;
+
+            let p5 = position
+                + &(&k1 * Self::A51 + &k2 * Self::A52 + &k3 * Self::A53 + &k4 * Self::A54) * h;
+            let k5 = Self::evaluate(velocity_field, interpolator, &p5)?;
+
+            let p6 = position
+                + &(&k1 * Self::A61
+                    + &k2 * Self::A62
+                    + &k3 * Self::A63
+                    + &k4 * Self::A64
+                    + &k5 * Self::A65)
+                    * h;
+            let k6 = Self::evaluate(velocity_field, interpolator, &p6)?;
+
+            let displacement =
+                &(&k1 * Self::B1 + &k3 * Self::B3 + &k4 * Self::B4 + &k5 * Self::B5 + &k6 * Self::B6) * h;
+            let next_position = position + &displacement;
+
+            let k7 = Self::evaluate(velocity_field, interpolator, &next_position)?;
+
+            let error = &(&k1 * Self::E1
+                + &k3 * Self::E3
+                + &k4 * Self::E4
+                + &k5 * Self::E5
+                + &k6 * Self::E6
+                + &k7 * Self::E7)
+                * h;
+
+            let state_scale = Vec3::new(
+                position[Dim3::X].abs().max(next_position[Dim3::X].abs()),
+                position[Dim3::Y].abs().max(next_position[Dim3::Y].abs()),
+                position[Dim3::Z].abs().max(next_position[Dim3::Z].abs()),
+            );
+            let tolerance = &state_scale * self.config.rel_tolerance
+                + Vec3::new(
+                    self.config.abs_tolerance,
+                    self.config.abs_tolerance,
+                    self.config.abs_tolerance,
+                );
+            let error_norm = ((error[Dim3::X] / tolerance[Dim3::X]).powi(2)
+                + (error[Dim3::Y] / tolerance[Dim3::Y]).powi(2)
+                + (error[Dim3::Z] / tolerance[Dim3::Z]).powi(2))
+            .sqrt()
+                / 3.0_f64.sqrt() as fdt;
+
+            let growth = if error_norm > 0.0 {
+                self.config.safety_factor * error_norm.powf(-1.0 / 5.0)
+            } else {
+                // A zero error (e.g. a uniform velocity field, or `h` not yet bootstrapped to a
+                // positive value) would otherwise divide by `h` directly; dividing by at least
+                // `min_step` instead keeps this finite and grows straight towards `max_step`.
+                self.config.max_step / h.max(self.config.min_step)
+            };
+            let new_h = (h * growth.clamp(0.2, 5.0)).clamp(self.config.min_step, self.config.max_step);
+
+            if error_norm <= 1.0 {
+                return Some((next_position, new_h));
+            }
+            h = new_h;
+        }
+
+        None
+    }
+}
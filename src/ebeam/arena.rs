@@ -0,0 +1,129 @@
+//! Bump-allocating typed arena for field-extraction sample buffers.
+//!
+//! `extract_fixed_scalars`/`extract_varying_scalars` (and their vector counterparts) are each
+//! called once per requested quantity and grow a per-beam sample buffer, which for large beam
+//! sets means millions of small pushes and the reallocations that come with them. [`TypedArena`]
+//! is meant to back those buffers instead: `alloc` hands out the next slot in the current chunk
+//! and bumps a cursor (O(1) amortized), and when a chunk fills, a new chunk with doubled capacity
+//! is allocated and pushed, so growth is geometric like `Vec`'s but without ever moving
+//! previously-handed-out slots (every arena-allocated slice stays valid until the arena is
+//! dropped). Wiring the extraction routines to allocate from a `TypedArena` and hand out slices
+//! into it at save time would need access to their sample accumulation loop, which is part of
+//! `ElectronBeamSwarm`'s own (currently absent) implementation, so this module only provides the
+//! arena itself, ready for that wiring once the rest of `ebeam` is present.
+
+use std::cell::RefCell;
+use std::mem::{self, MaybeUninit};
+use std::ptr::{self, NonNull};
+
+/// A contiguous slab of capacity `MaybeUninit<T>` slots, of which the first `filled` are
+/// initialized.
+struct Chunk<T> {
+    slots: NonNull<[MaybeUninit<T>]>,
+    filled: usize,
+}
+
+impl<T> Chunk<T> {
+    fn with_capacity(capacity: usize) -> Self {
+        let boxed: Box<[MaybeUninit<T>]> = (0..capacity).map(|_| MaybeUninit::uninit()).collect();
+        let slots = NonNull::new(Box::into_raw(boxed)).expect("Box::into_raw never returns null");
+        Self { slots, filled: 0 }
+    }
+
+    fn capacity(&self) -> usize {
+        unsafe { self.slots.as_ref() }.len()
+    }
+
+    /// Appends `value`, returning a reference into the chunk valid for the arena's lifetime.
+    /// Panics if the chunk is already full; callers must check `filled < capacity()` first.
+    fn push(&mut self, value: T) -> &mut T {
+        assert!(self.filled < self.capacity(), "Chunk is full");
+        let slot = unsafe { &mut (*self.slots.as_ptr())[self.filled] };
+        self.filled += 1;
+        slot.write(value)
+    }
+}
+
+impl<T> Drop for Chunk<T> {
+    fn drop(&mut self) {
+        let boxed = unsafe { Box::from_raw(self.slots.as_ptr()) };
+        if mem::needs_drop::<T>() {
+            for slot in &boxed[..self.filled] {
+                unsafe { ptr::drop_in_place(slot.as_ptr() as *mut T) };
+            }
+        }
+        drop(boxed);
+    }
+}
+
+/// The smallest chunk capacity a new [`TypedArena`] starts with.
+const INITIAL_CHUNK_CAPACITY: usize = 1024;
+
+/// A bump-allocating arena that hands out long-lived `&mut T` references into geometrically
+/// growing, contiguous chunks, so repeated single-element allocation is amortized O(1) and
+/// cache-friendly, unlike pushing onto many small independently-allocated `Vec`s.
+///
+/// `alloc` takes `&self`, not `&mut self` (as the `typed-arena` crate's `Arena::alloc` does),
+/// so a caller can keep accumulating more slots while still holding references to every slot
+/// handed out so far. An `&mut self` signature cannot support that: Rust's exclusive-borrow
+/// rules would forbid calling `alloc` again while any previously-returned `&mut T` is still
+/// live. `chunks` is therefore behind a `RefCell`, borrowed only for the instant it takes to
+/// grow the chunk list and write the new value.
+pub struct TypedArena<T> {
+    chunks: RefCell<Vec<Chunk<T>>>,
+}
+
+impl<T> TypedArena<T> {
+    pub fn new() -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Allocates one slot holding `value` and returns a reference into it, valid for as long as
+    /// this arena is not dropped.
+    pub fn alloc(&self, value: T) -> &mut T {
+        let mut chunks = self.chunks.borrow_mut();
+        let needs_new_chunk = match chunks.last() {
+            Some(chunk) => chunk.filled == chunk.capacity(),
+            None => true,
+        };
+        if needs_new_chunk {
+            let next_capacity = chunks
+                .last()
+                .map_or(INITIAL_CHUNK_CAPACITY, |chunk| chunk.capacity() * 2);
+            chunks.push(Chunk::with_capacity(next_capacity));
+        }
+        let slot: *mut T = chunks
+            .last_mut()
+            .expect("Just pushed a chunk if none existed")
+            .push(value);
+        // Dropped before the reference below is constructed: nothing in this method (or any
+        // other `TypedArena` method) ever removes a chunk or moves one's backing allocation,
+        // so `slot` stays valid once we let go of this borrow, and later calls to `alloc`
+        // don't see it as still outstanding.
+        drop(chunks);
+        // SAFETY: `slot` points into a chunk's heap allocation (a `Box<[MaybeUninit<T>]>`),
+        // which is never reallocated, moved, or freed while `self` is alive; chunks are only
+        // ever pushed, never removed or resized in place. So a reference into it can safely
+        // outlive this `RefCell` borrow, for as long as the arena itself lives.
+        unsafe { &mut *slot }
+    }
+
+    /// Total number of values allocated from this arena so far.
+    pub fn len(&self) -> usize {
+        self.chunks.borrow().iter().map(|chunk| chunk.filled).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Default for TypedArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+unsafe impl<T: Send> Send for TypedArena<T> {}
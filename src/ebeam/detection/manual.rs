@@ -3,13 +3,33 @@
 use super::ReconnectionSiteDetector;
 use crate::{
     field::CachingScalarFieldProvider3,
-    geometry::Idx3,
+    geometry::{Dim3, Idx3, Point3, Vec3},
     io::{snapshot::fdt, Verbosity},
     seeding::{manual::ManualSeeder3, Seeder3},
 };
+use rstar::{PointDistance, RTree, RTreeObject, AABB};
 use std::io;
 use std::path::Path;
 
+/// Strategy for picking the representative position of a cluster of
+/// near-duplicate reconnection site detections.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClusterRepresentative {
+    /// Use the centroid (component-wise mean) of the clustered positions.
+    Centroid,
+    /// Use the first detected position encountered for the cluster.
+    FirstSeen,
+}
+
+/// Configuration for merging near-duplicate reconnection site detections.
+#[derive(Clone, Copy, Debug)]
+pub struct ClusteringConfig {
+    /// Positions within this radius of each other are merged into one cluster.
+    pub radius: fdt,
+    /// How to pick the representative position for each cluster.
+    pub representative: ClusterRepresentative,
+}
+
 /// Detector reading the reconnection site positions from an input file.
 pub struct ManualReconnectionSiteDetector {
     seeder: ManualSeeder3,
@@ -25,6 +45,109 @@ impl ManualReconnectionSiteDetector {
             seeder: ManualSeeder3::new(input_file_path)?,
         })
     }
+
+    /// Creates a new manual reconnection site detector reading positions from the given
+    /// file, first merging any positions within `clustering.radius` of each other into a
+    /// single representative position.
+    ///
+    /// Clustering is backed by an `rstar` R-tree, giving O(log n) neighbor queries so this
+    /// scales well to the dense reconnection-site catalogs produced by automated detectors.
+    pub fn new_with_clustering(input_file_path: &Path, clustering: ClusteringConfig) -> io::Result<Self> {
+        let positions = ManualSeeder3::new(input_file_path)?.into_positions();
+        let clustered_positions =
+            cluster_positions(positions, clustering.radius, clustering.representative);
+        Ok(Self {
+            seeder: ManualSeeder3::from_positions(clustered_positions),
+        })
+    }
+}
+
+struct IndexedPosition {
+    index: usize,
+    position: Point3<fdt>,
+}
+
+impl RTreeObject for IndexedPosition {
+    type Envelope = AABB<[fdt; 3]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point([
+            self.position[Dim3::X],
+            self.position[Dim3::Y],
+            self.position[Dim3::Z],
+        ])
+    }
+}
+
+impl PointDistance for IndexedPosition {
+    fn distance_2(&self, point: &[fdt; 3]) -> fdt {
+        let dx = self.position[Dim3::X] - point[0];
+        let dy = self.position[Dim3::Y] - point[1];
+        let dz = self.position[Dim3::Z] - point[2];
+        dx * dx + dy * dy + dz * dz
+    }
+}
+
+/// Greedily merges positions within `radius` of each other into a single
+/// representative position, using an R-tree for efficient neighbor queries.
+fn cluster_positions(
+    positions: Vec<Point3<fdt>>,
+    radius: fdt,
+    representative: ClusterRepresentative,
+) -> Vec<Point3<fdt>> {
+    let radius_squared = radius * radius;
+
+    let tree = RTree::bulk_load(
+        positions
+            .iter()
+            .enumerate()
+            .map(|(index, position)| IndexedPosition {
+                index,
+                position: position.clone(),
+            })
+            .collect(),
+    );
+
+    let mut visited = vec![false; positions.len()];
+    let mut clustered_positions = Vec::new();
+
+    for seed_index in 0..positions.len() {
+        if visited[seed_index] {
+            continue;
+        }
+
+        let seed_position = &positions[seed_index];
+        let seed_coords = [
+            seed_position[Dim3::X],
+            seed_position[Dim3::Y],
+            seed_position[Dim3::Z],
+        ];
+
+        let cluster_indices: Vec<usize> = tree
+            .locate_within_distance(seed_coords, radius_squared)
+            .map(|neighbor| neighbor.index)
+            .filter(|&index| !visited[index])
+            .collect();
+
+        for &index in &cluster_indices {
+            visited[index] = true;
+        }
+
+        let representative_position = match representative {
+            ClusterRepresentative::FirstSeen => seed_position.clone(),
+            ClusterRepresentative::Centroid => {
+                let mut centroid = Vec3::zero();
+                for &index in &cluster_indices {
+                    centroid = centroid + positions[index].to_vec3();
+                }
+                (centroid / (cluster_indices.len() as fdt)).to_point3()
+            }
+        };
+
+        clustered_positions.push(representative_position);
+    }
+
+    clustered_positions
 }
 
 impl ReconnectionSiteDetector for ManualReconnectionSiteDetector {
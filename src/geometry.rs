@@ -1,9 +1,9 @@
 //! Geometric utility objects.
 
 use num;
-use std::ops::{Index, IndexMut, Add, Sub, Mul, Div};
+use std::ops::{Index, IndexMut, Add, Sub, Mul, Div, Neg};
 use ndarray::prelude::*;
-use serde::Serialize;
+use serde::{Serialize, Deserialize, Deserializer};
 
 /// Denotes the x-, y- or z-dimension.
 #[derive(Debug, Copy, Clone)]
@@ -68,6 +68,13 @@ impl<T> IndexMut<Dim3> for In3D<T> {
     fn index_mut(&mut self, dim: Dim3) -> &mut Self::Output { &mut self.0[dim as usize] }
 }
 
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for In3D<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let [x, y, z] = <[T; 3]>::deserialize(deserializer)?;
+        Ok(In3D([x, y, z]))
+    }
+}
+
 /// Represents any quantity with two dimensional components.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct In2D<T>([T; 2]);
@@ -89,35 +96,57 @@ impl<T> IndexMut<Dim2> for In2D<T> {
     fn index_mut(&mut self, dim: Dim2) -> &mut Self::Output { &mut self.0[dim as usize] }
 }
 
-/// A 3D vector.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for In2D<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let [x, y] = <[T; 2]>::deserialize(deserializer)?;
+        Ok(In2D([x, y]))
+    }
+}
+
+/// Marker for a vector or point whose physical unit is not (yet) tracked at compile time.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnknownUnit;
+
+/// A 3D vector, generic over a phantom unit marker `U` that prevents mixing
+/// quantities of different physical units (e.g. a position and a velocity)
+/// through `Add`/`Sub`/`dot`. Use [`TypedVec3::cast_unit`] to convert between units.
 #[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct Vec3<F: num::Float>(In3D<F>);
+#[serde(transparent)]
+pub struct TypedVec3<F: num::Float, U = UnknownUnit>(In3D<F>, #[serde(skip)] std::marker::PhantomData<U>);
+
+/// A 3D vector with no compile-time unit tracking.
+pub type Vec3<F> = TypedVec3<F, UnknownUnit>;
 
-impl<F: num::Float> Vec3<F> {
+impl<F: num::Float, U> TypedVec3<F, U> {
     /// Creates a new 3D vector given the three components.
-    pub fn new(x: F, y: F, z: F) -> Self { Vec3(In3D::new(x, y, z)) }
+    pub fn new(x: F, y: F, z: F) -> Self { TypedVec3(In3D::new(x, y, z), std::marker::PhantomData) }
 
     /// Creates a new zero vector.
-    pub fn zero() -> Self { Vec3::new(F::zero(), F::zero(), F::zero()) }
+    pub fn zero() -> Self { Self::new(F::zero(), F::zero(), F::zero()) }
 
     /// Creates a new vector from the given vector, which may have a different component type.
-    pub fn from<U: num::Float>(other: &Vec3<U>) -> Self {
-        Vec3::new(F::from(other[X]).unwrap(), F::from(other[Y]).unwrap(), F::from(other[Z]).unwrap())
+    pub fn from<G: num::Float>(other: &TypedVec3<G, U>) -> Self {
+        Self::new(F::from(other[X]).unwrap(), F::from(other[Y]).unwrap(), F::from(other[Z]).unwrap())
+    }
+
+    /// Reinterprets the vector as having a different unit, without changing its components.
+    pub fn cast_unit<V>(&self) -> TypedVec3<F, V> {
+        TypedVec3::new(self[X], self[Y], self[Z])
     }
 
     /// Constructs a new point from the vector components.
-    pub fn to_point3(&self) -> Point3<F> {
-        Point3::new(self[X], self[Y], self[Z])
+    pub fn to_point3(&self) -> TypedPoint3<F, U> {
+        TypedPoint3::new(self[X], self[Y], self[Z])
     }
 
     /// Constructs a new vector from the absolute values of the vector components.
     pub fn abs(&self) -> Self {
-        Vec3::new(F::abs(self[X]), F::abs(self[Y]), F::abs(self[Z]))
+        Self::new(F::abs(self[X]), F::abs(self[Y]), F::abs(self[Z]))
     }
 
     /// Constructs a new vector by taking the component-wise max with the given vector.
     pub fn max_with(&self, other: &Self) -> Self {
-        Vec3::new(F::max(self[X], other[X]), F::max(self[Y], other[Y]), F::max(self[Z], other[Z]))
+        Self::new(F::max(self[X], other[X]), F::max(self[Y], other[Y]), F::max(self[Z], other[Z]))
     }
 
     /// Computes the squared length of the vector.
@@ -135,7 +164,7 @@ impl<F: num::Float> Vec3<F> {
         self[X] == F::zero() && self[Y] == F::zero() && self[Z] == F::zero()
     }
 
-    /// Computes the dot product of the vector with another vector.
+    /// Computes the dot product of the vector with another vector of the same unit.
     pub fn dot(&self, other: &Self) -> F {
         self[X]*other[X] +
         self[Y]*other[Y] +
@@ -158,19 +187,55 @@ impl<F: num::Float> Vec3<F> {
         self[Y] = -self[Y];
         self[Z] = -self[Z];
     }
+
+    /// Computes the cross product of the vector with another vector of the same unit.
+    pub fn cross(&self, other: &Self) -> Self {
+        Self::new(self[Y]*other[Z] - self[Z]*other[Y],
+                  self[Z]*other[X] - self[X]*other[Z],
+                  self[X]*other[Y] - self[Y]*other[X])
+    }
+
+    /// Returns a normalized copy of the vector, leaving the original unchanged.
+    pub fn normalized(&self) -> Self {
+        let mut normalized = self.clone();
+        normalized.normalize();
+        normalized
+    }
+
+    /// Computes the angle to another vector, in the range `[0, pi]`.
+    ///
+    /// Computed via `atan2(cross.length(), dot)` rather than `acos(dot)`
+    /// for better numerical stability near parallel and antiparallel vectors.
+    pub fn angle_to(&self, other: &Self) -> F {
+        self.cross(other).length().atan2(self.dot(other))
+    }
+
+    /// Linearly interpolates between this vector and another by the factor `t`.
+    pub fn lerp(&self, other: &Self, t: F) -> Self {
+        self + &(other - self)*t
+    }
+}
+
+// Deserializes the bare `In3D` (`[x, y, z]`), matching the `#[serde(transparent)]` `Serialize`
+// impl above; changing one without the other would break the serialize/deserialize round-trip.
+impl<'de, F: num::Float + Deserialize<'de>, U> Deserialize<'de> for TypedVec3<F, U> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let components = In3D::<F>::deserialize(deserializer)?;
+        Ok(TypedVec3(components, std::marker::PhantomData))
+    }
 }
 
-impl<F: num::Float> Index<Dim3> for Vec3<F> {
+impl<F: num::Float, U> Index<Dim3> for TypedVec3<F, U> {
     type Output = F;
     fn index(&self, dim: Dim3) -> &Self::Output { &self.0[dim] }
 }
 
-impl<F: num::Float> IndexMut<Dim3> for Vec3<F> {
+impl<F: num::Float, U> IndexMut<Dim3> for TypedVec3<F, U> {
     fn index_mut(&mut self, dim: Dim3) -> &mut Self::Output { &mut self.0[dim] }
 }
 
-impl<'a, F: num::Float> Add<&'a Vec3<F>> for &'a Vec3<F> {
-    type Output = Vec3<F>;
+impl<'a, F: num::Float, U> Add<&'a TypedVec3<F, U>> for &'a TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn add(self, other: Self) -> Self::Output {
         Self::Output::new(self[X] + other[X],
                           self[Y] + other[Y],
@@ -178,23 +243,23 @@ impl<'a, F: num::Float> Add<&'a Vec3<F>> for &'a Vec3<F> {
     }
 }
 
-impl<F: num::Float> Add<Vec3<F>> for &Vec3<F> {
-    type Output = Vec3<F>;
-    fn add(self, other: Vec3<F>) -> Self::Output { self + &other }
+impl<F: num::Float, U> Add<TypedVec3<F, U>> for &TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
+    fn add(self, other: TypedVec3<F, U>) -> Self::Output { self + &other }
 }
 
-impl<F: num::Float> Add<Vec3<F>> for Vec3<F> {
+impl<F: num::Float, U> Add<TypedVec3<F, U>> for TypedVec3<F, U> {
     type Output = Self;
     fn add(self, other: Self) -> Self::Output { &self + &other }
 }
 
-impl<F: num::Float> Add<&Vec3<F>> for Vec3<F> {
+impl<F: num::Float, U> Add<&TypedVec3<F, U>> for TypedVec3<F, U> {
     type Output = Self;
     fn add(self, other: &Self) -> Self::Output { &self + other }
 }
 
-impl<'a, F: num::Float> Sub<&'a Vec3<F>> for &'a Vec3<F> {
-    type Output = Vec3<F>;
+impl<'a, F: num::Float, U> Sub<&'a TypedVec3<F, U>> for &'a TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn sub(self, other: Self) -> Self::Output {
         Self::Output::new(self[X] - other[X],
                           self[Y] - other[Y],
@@ -202,23 +267,23 @@ impl<'a, F: num::Float> Sub<&'a Vec3<F>> for &'a Vec3<F> {
     }
 }
 
-impl<F: num::Float> Sub<Vec3<F>> for &Vec3<F> {
-    type Output = Vec3<F>;
-    fn sub(self, other: Vec3<F>) -> Self::Output { self - &other }
+impl<F: num::Float, U> Sub<TypedVec3<F, U>> for &TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
+    fn sub(self, other: TypedVec3<F, U>) -> Self::Output { self - &other }
 }
 
-impl<F: num::Float> Sub<Vec3<F>> for Vec3<F> {
+impl<F: num::Float, U> Sub<TypedVec3<F, U>> for TypedVec3<F, U> {
     type Output = Self;
     fn sub(self, other: Self) -> Self::Output { &self - &other }
 }
 
-impl<F: num::Float> Sub<&Vec3<F>> for Vec3<F> {
+impl<F: num::Float, U> Sub<&TypedVec3<F, U>> for TypedVec3<F, U> {
     type Output = Self;
     fn sub(self, other: &Self) -> Self::Output { &self - other }
 }
 
-impl<F: num::Float> Mul<F> for &Vec3<F> {
-    type Output = Vec3<F>;
+impl<F: num::Float, U> Mul<F> for &TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn mul(self, factor: F) -> Self::Output {
         Self::Output::new(factor*self[X],
                           factor*self[Y],
@@ -226,13 +291,13 @@ impl<F: num::Float> Mul<F> for &Vec3<F> {
     }
 }
 
-impl<F: num::Float> Mul<F> for Vec3<F> {
+impl<F: num::Float, U> Mul<F> for TypedVec3<F, U> {
     type Output = Self;
     fn mul(self, factor: F) -> Self::Output { &self*factor }
 }
 
-impl<F: num::Float> Div<F> for &Vec3<F> {
-    type Output = Vec3<F>;
+impl<F: num::Float, U> Div<F> for &TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn div(self, divisor: F) -> Self::Output {
         #![allow(clippy::suspicious_arithmetic_impl)]
         let factor = divisor.recip();
@@ -240,11 +305,23 @@ impl<F: num::Float> Div<F> for &Vec3<F> {
     }
 }
 
-impl<F: num::Float> Div<F> for Vec3<F> {
+impl<F: num::Float, U> Div<F> for TypedVec3<F, U> {
     type Output = Self;
     fn div(self, divisor: F) -> Self::Output { &self/divisor }
 }
 
+impl<F: num::Float, U> Neg for &TypedVec3<F, U> {
+    type Output = TypedVec3<F, U>;
+    fn neg(self) -> Self::Output {
+        Self::Output::new(-self[X], -self[Y], -self[Z])
+    }
+}
+
+impl<F: num::Float, U> Neg for TypedVec3<F, U> {
+    type Output = Self;
+    fn neg(self) -> Self::Output { -&self }
+}
+
 /// A 2D vector.
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Vec2<F: num::Float>(In2D<F>);
@@ -311,6 +388,23 @@ impl<F: num::Float> Vec2<F> {
         self[Dim2::X] = -self[Dim2::X];
         self[Dim2::Y] = -self[Dim2::Y];
     }
+
+    /// Computes the (scalar) cross product of the vector with another vector,
+    /// i.e. the z-component of the 3D cross product of the vectors embedded in the xy-plane.
+    pub fn cross(&self, other: &Self) -> F {
+        self[Dim2::X]*other[Dim2::Y] - self[Dim2::Y]*other[Dim2::X]
+    }
+
+    /// Returns the vector rotated a quarter turn counter-clockwise.
+    pub fn perpendicular(&self) -> Self {
+        Vec2::new(-self[Dim2::Y], self[Dim2::X])
+    }
+}
+
+impl<'de, F: num::Float + Deserialize<'de>> Deserialize<'de> for Vec2<F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Vec2(In2D::<F>::deserialize(deserializer)?))
+    }
 }
 
 impl<F: num::Float> Index<Dim2> for Vec2<F> {
@@ -395,39 +489,59 @@ impl<F: num::Float> Div<F> for Vec2<F> {
     fn div(self, divisor: F) -> Self::Output { &self/divisor }
 }
 
-/// A 3D spatial coordinate.
+/// A 3D spatial coordinate, generic over a phantom unit marker `U` that prevents
+/// mixing quantities of different physical units through `Add`/`Sub`.
+/// Use [`TypedPoint3::cast_unit`] to convert between units.
 #[derive(Debug, Clone, PartialEq, Serialize)]
-pub struct Point3<F: num::Float>(In3D<F>);
+#[serde(transparent)]
+pub struct TypedPoint3<F: num::Float, U = UnknownUnit>(In3D<F>, #[serde(skip)] std::marker::PhantomData<U>);
+
+/// A 3D spatial coordinate with no compile-time unit tracking.
+pub type Point3<F> = TypedPoint3<F, UnknownUnit>;
 
-impl<F: num::Float> Point3<F> {
+impl<F: num::Float, U> TypedPoint3<F, U> {
     /// Creates a new 3D point given the three components.
-    pub fn new(x: F, y: F, z: F) -> Self { Point3(In3D::new(x, y, z)) }
+    pub fn new(x: F, y: F, z: F) -> Self { TypedPoint3(In3D::new(x, y, z), std::marker::PhantomData) }
 
     /// Creates a new point from the given point, which may have a different component type.
-    pub fn from<U: num::Float>(other: &Point3<U>) -> Self {
-        Point3::new(F::from(other[X]).unwrap(), F::from(other[Y]).unwrap(), F::from(other[Z]).unwrap())
+    pub fn from<G: num::Float>(other: &TypedPoint3<G, U>) -> Self {
+        Self::new(F::from(other[X]).unwrap(), F::from(other[Y]).unwrap(), F::from(other[Z]).unwrap())
+    }
+
+    /// Reinterprets the point as having a different unit, without changing its components.
+    pub fn cast_unit<V>(&self) -> TypedPoint3<F, V> {
+        TypedPoint3::new(self[X], self[Y], self[Z])
     }
 
     /// Constructs a new vector from the point components.
-    pub fn to_vec3(&self) -> Vec3<F> {
-        Vec3::new(self[X], self[Y], self[Z])
+    pub fn to_vec3(&self) -> TypedVec3<F, U> {
+        TypedVec3::new(self[X], self[Y], self[Z])
     }
 
     /// Creates a new 3D point with all components set to zero.
     pub fn origin() -> Self { Self::new(F::zero(), F::zero(), F::zero()) }
 }
 
-impl<F: num::Float> Index<Dim3> for Point3<F> {
+// Deserializes the bare `In3D` (`[x, y, z]`), matching the `#[serde(transparent)]` `Serialize`
+// impl above; changing one without the other would break the serialize/deserialize round-trip.
+impl<'de, F: num::Float + Deserialize<'de>, U> Deserialize<'de> for TypedPoint3<F, U> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        let components = In3D::<F>::deserialize(deserializer)?;
+        Ok(TypedPoint3(components, std::marker::PhantomData))
+    }
+}
+
+impl<F: num::Float, U> Index<Dim3> for TypedPoint3<F, U> {
     type Output = F;
     fn index(&self, dim: Dim3) -> &Self::Output { &self.0[dim] }
 }
 
-impl<F: num::Float> IndexMut<Dim3> for Point3<F> {
+impl<F: num::Float, U> IndexMut<Dim3> for TypedPoint3<F, U> {
     fn index_mut(&mut self, dim: Dim3) -> &mut Self::Output { &mut self.0[dim] }
 }
 
-impl<'a, F: num::Float> Sub<&'a Self> for &'a Point3<F> {
-    type Output = Vec3<F>;
+impl<'a, F: num::Float, U> Sub<&'a Self> for &'a TypedPoint3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn sub(self, other: &Self) -> Self::Output {
         Self::Output::new(self[X] - other[X],
                           self[Y] - other[Y],
@@ -435,57 +549,155 @@ impl<'a, F: num::Float> Sub<&'a Self> for &'a Point3<F> {
     }
 }
 
-impl<F: num::Float> Sub<Self> for Point3<F> {
-    type Output = Vec3<F>;
+impl<F: num::Float, U> Sub<Self> for TypedPoint3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn sub(self, other: Self) -> Self::Output { &self - &other }
 }
 
-impl<F: num::Float> Sub<Self> for &Point3<F> {
-    type Output = Vec3<F>;
+impl<F: num::Float, U> Sub<Self> for &TypedPoint3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn sub(self, other: Self) -> Self::Output { #![allow(clippy::op_ref)] self - &other }
 }
 
-impl<F: num::Float> Sub<&Self> for Point3<F> {
-    type Output = Vec3<F>;
+impl<F: num::Float, U> Sub<&Self> for TypedPoint3<F, U> {
+    type Output = TypedVec3<F, U>;
     fn sub(self, other: &Self) -> Self::Output { &self - other }
 }
 
-impl<'a, F: num::Float> Add<&'a Vec3<F>> for &'a Point3<F> {
-    type Output = Point3<F>;
-    fn add(self, vector: &Vec3<F>) -> Self::Output {
+impl<'a, F: num::Float, U> Add<&'a TypedVec3<F, U>> for &'a TypedPoint3<F, U> {
+    type Output = TypedPoint3<F, U>;
+    fn add(self, vector: &TypedVec3<F, U>) -> Self::Output {
         Self::Output::new(self[X] + vector[X],
                           self[Y] + vector[Y],
                           self[Z] + vector[Z])
     }
 }
 
-impl<F: num::Float> Add<Vec3<F>> for Point3<F> {
+impl<F: num::Float, U> Add<TypedVec3<F, U>> for TypedPoint3<F, U> {
     type Output = Self;
-    fn add(self, vector: Vec3<F>) -> Self::Output { &self + &vector }
+    fn add(self, vector: TypedVec3<F, U>) -> Self::Output { &self + &vector }
 }
 
-impl<F: num::Float> Add<Vec3<F>> for &Point3<F> {
-    type Output = Point3<F>;
-    fn add(self, vector: Vec3<F>) -> Self::Output { self + &vector }
+impl<F: num::Float, U> Add<TypedVec3<F, U>> for &TypedPoint3<F, U> {
+    type Output = TypedPoint3<F, U>;
+    fn add(self, vector: TypedVec3<F, U>) -> Self::Output { self + &vector }
 }
 
-impl<'a, F: num::Float> Sub<&'a Vec3<F>> for &'a Point3<F> {
-    type Output = Point3<F>;
-    fn sub(self, vector: &Vec3<F>) -> Self::Output {
+impl<'a, F: num::Float, U> Sub<&'a TypedVec3<F, U>> for &'a TypedPoint3<F, U> {
+    type Output = TypedPoint3<F, U>;
+    fn sub(self, vector: &TypedVec3<F, U>) -> Self::Output {
         Self::Output::new(self[X] - vector[X],
                           self[Y] - vector[Y],
                           self[Z] - vector[Z])
     }
 }
 
-impl<F: num::Float> Sub<Vec3<F>> for Point3<F> {
+impl<F: num::Float, U> Sub<TypedVec3<F, U>> for TypedPoint3<F, U> {
     type Output = Self;
-    fn sub(self, vector: Vec3<F>) -> Self::Output { &self - &vector }
+    fn sub(self, vector: TypedVec3<F, U>) -> Self::Output { &self - &vector }
+}
+
+impl<F: num::Float, U> Sub<TypedVec3<F, U>> for &TypedPoint3<F, U> {
+    type Output = TypedPoint3<F, U>;
+    fn sub(self, vector: TypedVec3<F, U>) -> Self::Output { self - &vector }
+}
+
+/// A 3D affine transform, stored as a row-major 4×4 homogeneous matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform3<F: num::Float>([[F; 4]; 4]);
+
+impl<F: num::Float> Transform3<F> {
+    /// Creates the identity transform.
+    pub fn identity() -> Self {
+        let mut matrix = [[F::zero(); 4]; 4];
+        for i in 0..4 {
+            matrix[i][i] = F::one();
+        }
+        Self(matrix)
+    }
+
+    /// Creates a transform rotating by the given angle (in radians) around the given axis,
+    /// using the Rodrigues rotation formula.
+    pub fn rotation(axis: &Vec3<F>, angle: F) -> Self {
+        let u = axis.normalized();
+        let (ux, uy, uz) = (u[X], u[Y], u[Z]);
+        let c = angle.cos();
+        let s = angle.sin();
+        let one_minus_c = F::one() - c;
+
+        let mut transform = Self::identity();
+        transform.0[0][0] = c + ux*ux*one_minus_c;
+        transform.0[0][1] = ux*uy*one_minus_c - uz*s;
+        transform.0[0][2] = ux*uz*one_minus_c + uy*s;
+        transform.0[1][0] = uy*ux*one_minus_c + uz*s;
+        transform.0[1][1] = c + uy*uy*one_minus_c;
+        transform.0[1][2] = uy*uz*one_minus_c - ux*s;
+        transform.0[2][0] = uz*ux*one_minus_c - uy*s;
+        transform.0[2][1] = uz*uy*one_minus_c + ux*s;
+        transform.0[2][2] = c + uz*uz*one_minus_c;
+        transform
+    }
+
+    /// Creates a transform translating by the given vector.
+    pub fn translation(translation: &Vec3<F>) -> Self {
+        let mut transform = Self::identity();
+        transform.0[0][3] = translation[X];
+        transform.0[1][3] = translation[Y];
+        transform.0[2][3] = translation[Z];
+        transform
+    }
+
+    /// Creates a transform scaling independently along each axis.
+    pub fn scaling(factors: &Vec3<F>) -> Self {
+        let mut transform = Self::identity();
+        transform.0[0][0] = factors[X];
+        transform.0[1][1] = factors[Y];
+        transform.0[2][2] = factors[Z];
+        transform
+    }
+
+    /// Applies the linear (rotation/scale) part of the transform to a vector,
+    /// ignoring any translation.
+    pub fn transform_vec3(&self, vector: &Vec3<F>) -> Vec3<F> {
+        let m = &self.0;
+        Vec3::new(
+            m[0][0]*vector[X] + m[0][1]*vector[Y] + m[0][2]*vector[Z],
+            m[1][0]*vector[X] + m[1][1]*vector[Y] + m[1][2]*vector[Z],
+            m[2][0]*vector[X] + m[2][1]*vector[Y] + m[2][2]*vector[Z],
+        )
+    }
+
+    /// Applies the full affine transform to a point, including translation.
+    pub fn transform_point3(&self, point: &Point3<F>) -> Point3<F> {
+        let m = &self.0;
+        Point3::new(
+            m[0][0]*point[X] + m[0][1]*point[Y] + m[0][2]*point[Z] + m[0][3],
+            m[1][0]*point[X] + m[1][1]*point[Y] + m[1][2]*point[Z] + m[1][3],
+            m[2][0]*point[X] + m[2][1]*point[Y] + m[2][2]*point[Z] + m[2][3],
+        )
+    }
+}
+
+impl<F: num::Float> Mul<&Transform3<F>> for &Transform3<F> {
+    type Output = Transform3<F>;
+    fn mul(self, other: &Transform3<F>) -> Self::Output {
+        let mut result = [[F::zero(); 4]; 4];
+        for row in 0..4 {
+            for col in 0..4 {
+                let mut sum = F::zero();
+                for k in 0..4 {
+                    sum = sum + self.0[row][k]*other.0[k][col];
+                }
+                result[row][col] = sum;
+            }
+        }
+        Transform3(result)
+    }
 }
 
-impl<F: num::Float> Sub<Vec3<F>> for &Point3<F> {
-    type Output = Point3<F>;
-    fn sub(self, vector: Vec3<F>) -> Self::Output { self - &vector }
+impl<F: num::Float> Mul<Transform3<F>> for Transform3<F> {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self::Output { &self*&other }
 }
 
 /// A 2D spatial coordinate.
@@ -510,6 +722,12 @@ impl<F: num::Float> Point2<F> {
     pub fn origin() -> Self { Self::new(F::zero(), F::zero()) }
 }
 
+impl<'de, F: num::Float + Deserialize<'de>> Deserialize<'de> for Point2<F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Point2(In2D::<F>::deserialize(deserializer)?))
+    }
+}
+
 impl<F: num::Float> Index<Dim2> for Point2<F> {
     type Output = F;
     fn index(&self, dim: Dim2) -> &Self::Output { &self.0[dim] }
@@ -644,6 +862,17 @@ impl<F: num::Float> Coords3<F> {
     pub fn point(&self, idx: usize) -> Point3<F> {
         Point3::new(self[X][idx], self[Y][idx], self[Z][idx])
     }
+
+    /// Computes the axis-aligned bounding box enclosing all the coordinates.
+    pub fn bounds(&self) -> Aabb3<F> {
+        Aabb3::from_coords(self)
+    }
+}
+
+impl<'de, F: num::Float + Deserialize<'de>> Deserialize<'de> for Coords3<F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Coords3(In3D::<Array1<F>>::deserialize(deserializer)?))
+    }
 }
 
 impl<F: num::Float> Index<Dim3> for Coords3<F> {
@@ -651,6 +880,105 @@ impl<F: num::Float> Index<Dim3> for Coords3<F> {
     fn index(&self, dim: Dim3) -> &Self::Output { &self.0[dim] }
 }
 
+/// An axis-aligned bounding box in 3D space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aabb3<F: num::Float> {
+    lower: Point3<F>,
+    upper: Point3<F>,
+}
+
+impl<F: num::Float> Aabb3<F> {
+    /// Creates a new bounding box from the given lower and upper corners.
+    ///
+    /// The caller must ensure that `lower` is component-wise less than or equal to `upper`.
+    pub fn new(lower: Point3<F>, upper: Point3<F>) -> Self {
+        Aabb3 { lower, upper }
+    }
+
+    /// Computes the bounding box enclosing the given coordinate arrays.
+    pub fn from_coords(coords: &Coords3<F>) -> Self {
+        let min_of = |dim| coords[dim].iter().cloned().fold(F::infinity(), F::min);
+        let max_of = |dim| coords[dim].iter().cloned().fold(F::neg_infinity(), F::max);
+        Aabb3::new(
+            Point3::new(min_of(X), min_of(Y), min_of(Z)),
+            Point3::new(max_of(X), max_of(Y), max_of(Z)),
+        )
+    }
+
+    /// Computes the bounding box enclosing the given set of points.
+    ///
+    /// Panics if `points` is empty.
+    pub fn from_points<'a, I>(points: I) -> Self
+    where I: IntoIterator<Item = &'a Point3<F>>, F: 'a {
+        let mut points = points.into_iter();
+        let first = points.next().expect("cannot compute bounds of an empty set of points");
+        let mut lower = first.clone();
+        let mut upper = first.clone();
+        for point in points {
+            for &dim in Dim3::slice().iter() {
+                lower[dim] = F::min(lower[dim], point[dim]);
+                upper[dim] = F::max(upper[dim], point[dim]);
+            }
+        }
+        Aabb3::new(lower, upper)
+    }
+
+    /// Returns the lower (minimum) corner of the bounding box.
+    pub fn min(&self) -> &Point3<F> { &self.lower }
+
+    /// Returns the upper (maximum) corner of the bounding box.
+    pub fn max(&self) -> &Point3<F> { &self.upper }
+
+    /// Returns the center point of the bounding box.
+    pub fn center(&self) -> Point3<F> {
+        self.lower.clone() + (&self.upper - &self.lower)*F::from(0.5).unwrap()
+    }
+
+    /// Returns the extents (side lengths) of the bounding box along each dimension.
+    pub fn extents(&self) -> Vec3<F> {
+        &self.upper - &self.lower
+    }
+
+    /// Whether the bounding box contains the given point.
+    pub fn contains(&self, point: &Point3<F>) -> bool {
+        Dim3::slice().iter().all(|&dim| point[dim] >= self.lower[dim] && point[dim] <= self.upper[dim])
+    }
+
+    /// Computes the intersection of this bounding box with another, if they overlap.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let lower = Point3::new(
+            F::max(self.lower[X], other.lower[X]),
+            F::max(self.lower[Y], other.lower[Y]),
+            F::max(self.lower[Z], other.lower[Z]),
+        );
+        let upper = Point3::new(
+            F::min(self.upper[X], other.upper[X]),
+            F::min(self.upper[Y], other.upper[Y]),
+            F::min(self.upper[Z], other.upper[Z]),
+        );
+        if Dim3::slice().iter().all(|&dim| lower[dim] <= upper[dim]) {
+            Some(Aabb3::new(lower, upper))
+        } else {
+            None
+        }
+    }
+
+    /// Computes the smallest bounding box enclosing both this bounding box and another.
+    pub fn union_with(&self, other: &Self) -> Self {
+        let lower = Point3::new(
+            F::min(self.lower[X], other.lower[X]),
+            F::min(self.lower[Y], other.lower[Y]),
+            F::min(self.lower[Z], other.lower[Z]),
+        );
+        let upper = Point3::new(
+            F::max(self.upper[X], other.upper[X]),
+            F::max(self.upper[Y], other.upper[Y]),
+            F::max(self.upper[Z], other.upper[Z]),
+        );
+        Aabb3::new(lower, upper)
+    }
+}
+
 /// 2D spatial coordinate arrays.
 #[derive(Debug, Clone, Serialize)]
 pub struct Coords2<F: num::Float>(In2D<Array1<F>>);
@@ -667,6 +995,12 @@ impl<F: num::Float> Coords2<F> {
     }
 }
 
+impl<'de, F: num::Float + Deserialize<'de>> Deserialize<'de> for Coords2<F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error> where D: Deserializer<'de> {
+        Ok(Coords2(In2D::<Array1<F>>::deserialize(deserializer)?))
+    }
+}
+
 impl<F: num::Float> Index<Dim2> for Coords2<F> {
     type Output = Array1<F>;
     fn index(&self, dim: Dim2) -> &Self::Output { &self.0[dim] }
@@ -722,4 +1056,95 @@ impl<'a, F: num::Float> CoordRefs2<'a, F> {
 impl<'a, F: num::Float> Index<Dim2> for CoordRefs2<'a, F> {
     type Output = &'a Array1<F>;
     fn index(&self, dim: Dim2) -> &Self::Output { &self.0[dim] }
+}
+
+/// Tolerant equality comparison for floating-point geometric types.
+///
+/// Unlike `PartialEq`, which requires exact equality, `approx_eq` allows a
+/// combined relative-and-absolute tolerance, making it suitable for comparing
+/// interpolated coordinates or other values subject to rounding error.
+pub trait ApproxEq: Sized {
+    /// The type of the tolerance used for comparison.
+    type Epsilon;
+
+    /// The default epsilon used by [`ApproxEq::approx_eq`].
+    fn default_epsilon() -> Self::Epsilon;
+
+    /// Whether `self` and `other` are equal within the given epsilon.
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool;
+
+    /// Whether `self` and `other` are equal within the default epsilon.
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.approx_eq_eps(other, Self::default_epsilon())
+    }
+}
+
+impl ApproxEq for f32 {
+    type Epsilon = Self;
+    fn default_epsilon() -> Self::Epsilon { 1e-5 }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        (self - other).abs() <= epsilon*self.abs().max(other.abs()).max(1.0)
+    }
+}
+
+impl ApproxEq for f64 {
+    type Epsilon = Self;
+    fn default_epsilon() -> Self::Epsilon { 1e-9 }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        (self - other).abs() <= epsilon*self.abs().max(other.abs()).max(1.0)
+    }
+}
+
+impl<F: num::Float + ApproxEq<Epsilon = F>, U> ApproxEq for TypedVec3<F, U> {
+    type Epsilon = F;
+    fn default_epsilon() -> Self::Epsilon { F::default_epsilon() }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Dim3::slice().iter().all(|&dim| self[dim].approx_eq_eps(&other[dim], epsilon))
+    }
+}
+
+impl<F: num::Float + ApproxEq<Epsilon = F>> ApproxEq for Vec2<F> {
+    type Epsilon = F;
+    fn default_epsilon() -> Self::Epsilon { F::default_epsilon() }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Dim2::slice().iter().all(|&dim| self[dim].approx_eq_eps(&other[dim], epsilon))
+    }
+}
+
+impl<F: num::Float + ApproxEq<Epsilon = F>, U> ApproxEq for TypedPoint3<F, U> {
+    type Epsilon = F;
+    fn default_epsilon() -> Self::Epsilon { F::default_epsilon() }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Dim3::slice().iter().all(|&dim| self[dim].approx_eq_eps(&other[dim], epsilon))
+    }
+}
+
+impl<F: num::Float + ApproxEq<Epsilon = F>> ApproxEq for Point2<F> {
+    type Epsilon = F;
+    fn default_epsilon() -> Self::Epsilon { F::default_epsilon() }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Dim2::slice().iter().all(|&dim| self[dim].approx_eq_eps(&other[dim], epsilon))
+    }
+}
+
+impl<F: num::Float + ApproxEq<Epsilon = F>> ApproxEq for Coords3<F> {
+    type Epsilon = F;
+    fn default_epsilon() -> Self::Epsilon { F::default_epsilon() }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Dim3::slice().iter().all(|&dim| {
+            self[dim].len() == other[dim].len()
+            && self[dim].iter().zip(other[dim].iter()).all(|(a, b)| a.approx_eq_eps(b, epsilon))
+        })
+    }
+}
+
+impl<F: num::Float + ApproxEq<Epsilon = F>> ApproxEq for Coords2<F> {
+    type Epsilon = F;
+    fn default_epsilon() -> Self::Epsilon { F::default_epsilon() }
+    fn approx_eq_eps(&self, other: &Self, epsilon: Self::Epsilon) -> bool {
+        Dim2::slice().iter().all(|&dim| {
+            self[dim].len() == other[dim].len()
+            && self[dim].iter().zip(other[dim].iter()).all(|(a, b)| a.approx_eq_eps(b, epsilon))
+        })
+    }
 }
\ No newline at end of file
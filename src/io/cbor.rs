@@ -0,0 +1,22 @@
+//! Serialization of data into CBOR (Concise Binary Object Representation).
+//!
+//! CBOR is a compact, self-describing binary encoding readable from any language with a
+//! CBOR library, giving callers a cross-language alternative to pickle output without
+//! pickle's security and version-fragility problems.
+
+use super::utils;
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+
+/// Serializes `data` into CBOR and writes it to the given writer.
+pub fn write_data_as_cbor<W: io::Write, T: Serialize>(writer: &mut W, data: &T) -> io::Result<()> {
+    serde_cbor::to_writer(writer, data)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+}
+
+/// Serializes `data` into CBOR and saves it at the given path.
+pub fn save_data_as_cbor<P: AsRef<Path>, T: Serialize>(output_file_path: P, data: &T) -> io::Result<()> {
+    let mut file = utils::create_file_and_required_directories(output_file_path)?;
+    write_data_as_cbor(&mut file, data)
+}
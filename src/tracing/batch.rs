@@ -0,0 +1,176 @@
+//! Parallel, streaming batch tracing of many field lines.
+
+use super::{
+    field_line::FieldLineTracer3,
+    stepping::{Stepper3, StepperFactory3},
+};
+use crate::{
+    geometry::Point3,
+    grid::Grid3,
+    interpolation::Interpolator3,
+    io::snapshot::{fdt, SnapshotCacher3, SnapshotProvider3},
+    seeding::Seeder3,
+};
+use crossbeam_channel::bounded;
+use rayon::prelude::*;
+
+/// A traced field line tagged with the index of the seed point it originated from.
+///
+/// Tagging lets a consumer reassemble results in deterministic seed order even
+/// though worker threads complete traces out of order. `data` is `None` when the seed
+/// point produced no trace (e.g. it lies outside the domain, or the line was degenerate),
+/// so a reassembler still sees every seed index and can advance past the gap instead of
+/// stalling forever waiting for one that will never arrive.
+pub struct IndexedTraceResult<D> {
+    /// Index of the seed point this trace was started from.
+    pub seed_index: usize,
+    /// The traced field line data, or `None` if tracing this seed point produced no line.
+    pub data: Option<D>,
+}
+
+/// Configuration for a parallel batch tracing run.
+#[derive(Clone, Debug)]
+pub struct BatchTracerConfig {
+    /// Number of rayon worker threads to use. `None` uses the global pool default.
+    pub n_threads: Option<usize>,
+    /// Capacity of the bounded channel connecting worker threads to the consumer.
+    pub channel_capacity: usize,
+    /// Number of completed traces between progress callback invocations.
+    pub progress_report_interval: usize,
+}
+
+impl Default for BatchTracerConfig {
+    fn default() -> Self {
+        Self {
+            n_threads: None,
+            channel_capacity: 128,
+            progress_report_interval: 100,
+        }
+    }
+}
+
+/// Traces all the field lines produced by a seeder across a rayon thread pool,
+/// streaming completed trajectories to `on_result` as soon as they are ready.
+///
+/// Unlike [`super::field_line::FieldLineSet3::trace`], which collects every
+/// line into memory before returning, this streams results through a bounded
+/// channel so a consumer can write them out incrementally without buffering
+/// the whole domain's worth of field lines at once. Each result is tagged
+/// with the index of its originating seed point, so a caller that needs
+/// deterministic output ordering can reassemble by `seed_index` (see
+/// [`OrderedReassembler`]).
+///
+/// # Type parameters
+///
+/// - `Sd`: Type of seeder.
+/// - `Tr`: Type of field line tracer.
+/// - `G`: Type of grid.
+/// - `I`: Type of interpolator.
+/// - `StF`: Type of stepper factory.
+pub fn trace_batch_streaming<Sd, Tr, G, P, I, StF>(
+    field_name: &str,
+    snapshot: &SnapshotCacher3<G, P>,
+    seeder: Sd,
+    tracer: &Tr,
+    interpolator: &I,
+    stepper_factory: &StF,
+    config: &BatchTracerConfig,
+    mut on_result: impl FnMut(IndexedTraceResult<Tr::Data>) + Send,
+    mut on_progress: impl FnMut(usize, usize),
+) where
+    Sd: Seeder3,
+    Tr: FieldLineTracer3 + Sync,
+    Tr::Data: Send,
+    G: Grid3<fdt>,
+    P: SnapshotProvider3<G> + Sync,
+    I: Interpolator3,
+    StF: StepperFactory3 + Sync,
+{
+    let start_positions: Vec<_> = seeder.into_par_iter().collect();
+    let number_of_traces = start_positions.len();
+
+    let mut pool_builder = rayon::ThreadPoolBuilder::new();
+    if let Some(n_threads) = config.n_threads {
+        pool_builder = pool_builder.num_threads(n_threads);
+    }
+    let pool = pool_builder
+        .build()
+        .expect("Failed to build thread pool for batch tracing");
+
+    let (sender, receiver) = bounded(config.channel_capacity);
+
+    pool.scope(|s| {
+        s.spawn(|_| {
+            start_positions
+                .into_par_iter()
+                .enumerate()
+                .for_each_with(sender, |sender, (seed_index, start_position)| {
+                    let data = tracer.trace(
+                        field_name,
+                        snapshot,
+                        interpolator,
+                        stepper_factory.produce(),
+                        &Point3::from(&start_position),
+                    );
+                    // Sent unconditionally, even when `data` is `None`, so every seed index
+                    // reaches the reassembler and a skipped seed cannot stall it.
+                    sender
+                        .send(IndexedTraceResult { seed_index, data })
+                        .expect("Receiver dropped before all traces were sent");
+                });
+        });
+
+        let mut n_completed = 0;
+        for result in receiver {
+            n_completed += 1;
+            if n_completed % config.progress_report_interval == 0 {
+                on_progress(n_completed, number_of_traces);
+            }
+            on_result(result);
+        }
+        on_progress(n_completed, number_of_traces);
+    });
+}
+
+/// Reassembles a stream of [`IndexedTraceResult`]s into deterministic seed-index order.
+///
+/// Buffers out-of-order results until the next expected index arrives, then
+/// flushes every contiguous result that has become available. Since every seed index is
+/// sent exactly once by [`trace_batch_streaming`] (with `data: None` standing in for a
+/// seed that produced no trace), a skipped seed still arrives and lets the reassembler
+/// advance past it, rather than buffering everything after it forever.
+pub struct OrderedReassembler<D> {
+    next_expected_index: usize,
+    pending: std::collections::HashMap<usize, Option<D>>,
+}
+
+impl<D> OrderedReassembler<D> {
+    /// Creates a new reassembler starting at seed index 0.
+    pub fn new() -> Self {
+        Self {
+            next_expected_index: 0,
+            pending: std::collections::HashMap::new(),
+        }
+    }
+
+    /// Accepts a result, invoking `on_ordered_result` for it and any
+    /// previously buffered results whose turn has now come. `on_ordered_result` is called
+    /// with `None` for a seed index whose trace was skipped.
+    pub fn accept(
+        &mut self,
+        result: IndexedTraceResult<D>,
+        mut on_ordered_result: impl FnMut(Option<D>),
+    ) {
+        self.pending.insert(result.seed_index, result.data);
+        while let Some(data) = self.pending.remove(&self.next_expected_index) {
+            on_ordered_result(data);
+            self.next_expected_index += 1;
+        }
+    }
+}
+
+impl<D> Default for OrderedReassembler<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,8 @@
 //! Field lines in vector fields.
 
 pub mod basic;
+pub mod quantized;
+pub mod streaming_binary;
 
 use super::{
     ftr,
@@ -27,7 +29,7 @@ use serde::{
     Serialize,
 };
 
-#[cfg(feature = "hdf5")]
+#[cfg(any(feature = "hdf5", feature = "arrow", feature = "parquet"))]
 use crate::io_result;
 #[cfg(feature = "hdf5")]
 use hdf5_rs as hdf5;
@@ -399,6 +401,18 @@ impl FieldLineSet3 {
         utils::save_data_as_json(output_file_path, &self)
     }
 
+    /// Serializes the field line data into CBOR format and writes to the given writer.
+    #[cfg(feature = "cbor")]
+    pub fn write_as_cbor<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        crate::io::cbor::write_data_as_cbor(writer, &self)
+    }
+
+    /// Serializes the field line data into CBOR format and saves at the given path.
+    #[cfg(feature = "cbor")]
+    pub fn save_as_cbor<P: AsRef<Path>>(&self, output_file_path: P) -> io::Result<()> {
+        crate::io::cbor::save_data_as_cbor(output_file_path, &self)
+    }
+
     /// Serializes the field line data into pickle format and writes to the given writer.
     ///
     /// All the field line data is saved as a single pickled structure.
@@ -476,23 +490,35 @@ impl FieldLineSet3 {
         self.write_as_combined_pickles(&mut file)
     }
 
-    /// Serializes the field line data into a custom binary format and writes to the given writer.
-    pub fn write_as_custom_binary<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+    /// Serializes the field line data into a custom binary format and writes to the given
+    /// writer, compressing the varying sections according to `compression_mode`.
+    pub fn write_as_custom_binary<W: io::Write>(
+        &self,
+        writer: &mut W,
+        compression_mode: quantized::VaryingDataCompressionMode,
+    ) -> io::Result<()> {
         write_field_line_data_as_custom_binary(
             writer,
             &self.lower_bounds,
             &self.upper_bounds,
             self.properties.clone(),
+            compression_mode,
         )
     }
 
-    /// Serializes the field line data into a custom binary format and saves at the given path.
-    pub fn save_as_custom_binary<P: AsRef<Path>>(&self, output_file_path: P) -> io::Result<()> {
+    /// Serializes the field line data into a custom binary format and saves at the given path,
+    /// compressing the varying sections according to `compression_mode`.
+    pub fn save_as_custom_binary<P: AsRef<Path>>(
+        &self,
+        output_file_path: P,
+        compression_mode: quantized::VaryingDataCompressionMode,
+    ) -> io::Result<()> {
         save_field_line_data_as_custom_binary(
             output_file_path,
             &self.lower_bounds,
             &self.upper_bounds,
             self.properties.clone(),
+            compression_mode,
         )
         .map(|_| ())
     }
@@ -514,24 +540,36 @@ impl FieldLineSet3 {
     }
 
     /// Serializes the field line data into a custom binary format and writes to the given writer,
-    /// consuming the field line set in the process.
-    pub fn write_into_custom_binary<W: io::Write>(self, writer: &mut W) -> io::Result<()> {
+    /// consuming the field line set in the process and compressing the varying sections
+    /// according to `compression_mode`.
+    pub fn write_into_custom_binary<W: io::Write>(
+        self,
+        writer: &mut W,
+        compression_mode: quantized::VaryingDataCompressionMode,
+    ) -> io::Result<()> {
         write_field_line_data_as_custom_binary(
             writer,
             &self.lower_bounds,
             &self.upper_bounds,
             self.properties,
+            compression_mode,
         )
     }
 
     /// Serializes the field line data into a custom binary format and saves at the given path,
-    /// consuming the field line set in the process.
-    pub fn save_into_custom_binary<P: AsRef<Path>>(self, output_file_path: P) -> io::Result<()> {
+    /// consuming the field line set in the process and compressing the varying sections
+    /// according to `compression_mode`.
+    pub fn save_into_custom_binary<P: AsRef<Path>>(
+        self,
+        output_file_path: P,
+        compression_mode: quantized::VaryingDataCompressionMode,
+    ) -> io::Result<()> {
         save_field_line_data_as_custom_binary(
             output_file_path,
             &self.lower_bounds,
             &self.upper_bounds,
             self.properties,
+            compression_mode,
         )
         .map(|_| ())
     }
@@ -552,6 +590,87 @@ impl FieldLineSet3 {
             drop_id,
         )
     }
+
+    /// Serializes the field line data into an Arrow IPC file and writes it to the given writer.
+    ///
+    /// Each fixed scalar quantity and each component of each fixed vector quantity becomes a
+    /// `Float64` column with one row per field line, while each varying scalar and vector
+    /// quantity becomes a `List<Float64>` column with one list element per field line, of a
+    /// length matching that line's path. `lower_bounds`, `upper_bounds` and
+    /// `number_of_field_lines` are stored as schema-level key/value metadata.
+    #[cfg(feature = "arrow")]
+    pub fn write_as_arrow_ipc<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write_field_line_data_as_arrow_ipc(
+            writer,
+            &self.lower_bounds,
+            &self.upper_bounds,
+            &self.properties,
+        )
+    }
+
+    /// Serializes the field line data into an Arrow IPC file and saves it at the given path.
+    #[cfg(feature = "arrow")]
+    pub fn save_as_arrow_ipc<P: AsRef<Path>>(&self, output_file_path: P) -> io::Result<()> {
+        let mut file = utils::create_file_and_required_directories(output_file_path)?;
+        self.write_as_arrow_ipc(&mut file)
+    }
+
+    /// Builds an in-memory Arrow `RecordBatch` from the field line data, using the same
+    /// fixed/varying-quantity-to-column mapping as [`write_as_arrow_ipc`](Self::write_as_arrow_ipc),
+    /// without going through IPC serialization. Hand this straight to DataFusion (e.g.
+    /// `MemTable::try_new`) for SQL-style filtering of field lines with no second parse.
+    #[cfg(feature = "arrow")]
+    pub fn to_record_batch(&self) -> io::Result<arrow::record_batch::RecordBatch> {
+        build_field_line_record_batch(&self.lower_bounds, &self.upper_bounds, &self.properties)
+            .map(|(_, batch)| batch)
+    }
+
+    /// Serializes the field line data into a quantized, bit-packed variant of the custom
+    /// binary format and writes it to the given writer.
+    ///
+    /// Every fixed/varying quantity is quantized independently to `bit_width` bits per
+    /// value, trading precision for a several-fold reduction in output size. See
+    /// [`quantized`] for details of the quantization scheme.
+    pub fn write_as_quantized_custom_binary<W: io::Write>(
+        &self,
+        writer: &mut W,
+        bit_width: quantized::BitWidth,
+    ) -> io::Result<()> {
+        write_field_line_data_as_quantized_custom_binary(
+            writer,
+            &self.properties,
+            bit_width,
+        )
+    }
+
+    /// Serializes the field line data into a quantized, bit-packed variant of the custom
+    /// binary format and saves it at the given path.
+    pub fn save_as_quantized_custom_binary<P: AsRef<Path>>(
+        &self,
+        output_file_path: P,
+        bit_width: quantized::BitWidth,
+    ) -> io::Result<()> {
+        let mut file = utils::create_file_and_required_directories(output_file_path)?;
+        self.write_as_quantized_custom_binary(&mut file, bit_width)
+    }
+
+    /// Serializes the field line data into an Apache Parquet file and saves it at the
+    /// given path, writing `row_group_size` field lines per row group. The flattened
+    /// columns for the whole set are built in memory before the first row group is written.
+    #[cfg(feature = "parquet")]
+    pub fn save_as_parquet<P: AsRef<Path>>(
+        &self,
+        output_file_path: P,
+        row_group_size: usize,
+        compression: parquet::basic::Compression,
+    ) -> io::Result<()> {
+        save_field_line_data_as_parquet(
+            output_file_path,
+            self.properties.clone(),
+            row_group_size,
+            compression,
+        )
+    }
 }
 
 impl Default for FieldLineSetProperties3 {
@@ -588,28 +707,82 @@ impl Serialize for FieldLineSet3 {
 }
 
 /// Writes the given field line data in a custom binary format at the
-/// given path.
+/// given path, applying `compression_mode` to the varying scalar/vector sections.
 pub fn save_field_line_data_as_custom_binary<P: AsRef<Path>>(
     output_file_path: P,
     lower_bounds: &Vec3<ftr>,
     upper_bounds: &Vec3<ftr>,
     properties: FieldLineSetProperties3,
+    compression_mode: quantized::VaryingDataCompressionMode,
 ) -> io::Result<fs::File> {
     let mut file = utils::create_file_and_required_directories(output_file_path)?;
-    write_field_line_data_as_custom_binary(&mut file, lower_bounds, upper_bounds, properties)?;
+    write_field_line_data_as_custom_binary(
+        &mut file,
+        lower_bounds,
+        upper_bounds,
+        properties,
+        compression_mode,
+    )?;
     Ok(file)
 }
 
-/// Writes the given field line data in a custom binary format into
-/// the given writer.
+/// Field line count above which [`write_field_line_data_as_custom_binary`] switches from the
+/// in-memory, rayon-parallel flattening path to the bounded-memory streaming path.
+const STREAMING_FIELD_LINE_THRESHOLD: usize = 100_000;
+
+/// Writes the given field line data in a custom binary format into the given writer.
+///
+/// `compression_mode` selects how the (usually dominant) varying scalar/vector sections are
+/// encoded; see [`quantized::VaryingDataCompressionMode`]. Fixed quantities are always
+/// written as raw floats, since they are tiny compared to the varying sections.
+///
+/// Sets with at least [`STREAMING_FIELD_LINE_THRESHOLD`] field lines are written through
+/// [`write_field_line_data_as_custom_binary_streaming`], which packs and flushes each
+/// quantity in fixed-size batches of field lines so peak memory stays bounded regardless of
+/// set size. Smaller sets keep using
+/// [`write_field_line_data_as_custom_binary_in_memory`], whose rayon-parallel flattening is
+/// faster when the whole set comfortably fits in memory anyway. The on-disk layout is
+/// identical either way.
 pub fn write_field_line_data_as_custom_binary<W: io::Write>(
     writer: &mut W,
     lower_bounds: &Vec3<ftr>,
     upper_bounds: &Vec3<ftr>,
     properties: FieldLineSetProperties3,
+    compression_mode: quantized::VaryingDataCompressionMode,
+) -> io::Result<()> {
+    if properties.number_of_field_lines >= STREAMING_FIELD_LINE_THRESHOLD {
+        write_field_line_data_as_custom_binary_streaming(
+            writer,
+            lower_bounds,
+            upper_bounds,
+            properties,
+            compression_mode,
+        )
+    } else {
+        write_field_line_data_as_custom_binary_in_memory(
+            writer,
+            lower_bounds,
+            upper_bounds,
+            properties,
+            compression_mode,
+        )
+    }
+}
+
+/// Writes the given field line data in a custom binary format into the given writer,
+/// flattening each quantity into a full-set-sized buffer up front (in parallel over rayon)
+/// before writing it out. Peak memory is roughly double the data set; prefer
+/// [`write_field_line_data_as_custom_binary_streaming`] for very large sets.
+fn write_field_line_data_as_custom_binary_in_memory<W: io::Write>(
+    writer: &mut W,
+    lower_bounds: &Vec3<ftr>,
+    upper_bounds: &Vec3<ftr>,
+    properties: FieldLineSetProperties3,
+    compression_mode: quantized::VaryingDataCompressionMode,
 ) -> io::Result<()> {
     // Field line file format:
     // [HEADER]
+    // format_version: u64
     // float_size: u64
     // number_of_field_lines: u64
     // number_of_field_line_elements: u64
@@ -617,16 +790,25 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
     // number_of_fixed_vector_quantities: u64
     // number_of_varying_scalar_quantities: u64
     // number_of_varying_vector_quantities: u64
+    // compression_mode: u64 (format_version >= 3; tag of the `VaryingDataCompressionMode` used
+    //     for every varying section below, see `quantized::VaryingDataCompressionMode::tag`)
     // bounds: [ftr; 6]
     // names: string with each name followed by a newline
-    // start_indices_of_field_line_elements: [u64; number_of_field_lines]
+    // [FIELD LINE LENGTH INDEX] (format_version >= 2)
+    // inner_width: u8 (1, 2, 4 or 8: smallest width that fits the longest field line)
+    // field_line_lengths: [uN; number_of_field_lines], N = inner_width*8
+    //     (the start index of field line element i is the prefix sum of lengths 0..i;
+    //     reconstructing it takes one pass and avoids storing it outright)
     // [BODY]
-    // flat_fixed_scalar_values:   [ftr: number_of_fixed_scalar_quantities*number_of_field_lines  ]
-    // flat_fixed_vector_values:   [ftr: number_of_fixed_vector_quantities*number_of_field_lines*3]
-    // flat_varying_scalar_values: [ftr: number_of_varying_scalar_quantities*number_of_field_line_elements  ]
-    // flat_varying_vector_values: [ftr: number_of_varying_vector_quantities*number_of_field_line_elements*3]
+    // flat_fixed_scalar_values: [ftr: number_of_fixed_scalar_quantities*number_of_field_lines  ]
+    // flat_fixed_vector_values: [ftr: number_of_fixed_vector_quantities*number_of_field_lines*3]
+    // varying_scalar_sections: one per varying scalar quantity (format_version >= 3), each
+    //     written by `quantized::write_varying_quantity` (a mode tag followed by that
+    //     quantity's, possibly quantized and/or zstd-compressed, payload)
+    // varying_vector_sections: the same, one per component of each varying vector quantity
 
     const ENDIANNESS: Endianness = Endianness::Little;
+    const FORMAT_VERSION: u64 = 3;
 
     let FieldLineSetProperties3 {
         number_of_field_lines,
@@ -641,50 +823,17 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
     let number_of_varying_scalar_quantities = varying_scalar_values.len();
     let number_of_varying_vector_quantities = varying_vector_values.len();
 
-    let (number_of_field_line_elements, start_indices_of_field_line_elements) =
-        if varying_scalar_values.is_empty() {
-            if varying_vector_values.is_empty() {
-                (0, Vec::new())
-            } else {
-                let (_, varying_vectors) = varying_vector_values.iter().next().unwrap();
-
-                let number_of_field_line_elements: usize =
-                    varying_vectors.iter().map(|vec| vec.len()).sum();
-
-                let start_indices_of_field_line_elements: Vec<_> = varying_vectors
-                    .iter()
-                    .scan(0, |count, vec| {
-                        let idx = *count;
-                        *count += vec.len();
-                        Some(idx as u64)
-                    })
-                    .collect();
-
-                (
-                    number_of_field_line_elements,
-                    start_indices_of_field_line_elements,
-                )
-            }
-        } else {
-            let (_, varying_scalars) = varying_scalar_values.iter().next().unwrap();
-
-            let number_of_field_line_elements: usize =
-                varying_scalars.iter().map(|vec| vec.len()).sum();
-
-            let start_indices_of_field_line_elements: Vec<_> = varying_scalars
-                .iter()
-                .scan(0, |count, vec| {
-                    let idx = *count;
-                    *count += vec.len();
-                    Some(idx as u64)
-                })
-                .collect();
+    let field_line_lengths: Vec<usize> = if let Some((_, varying_scalars)) =
+        varying_scalar_values.iter().next()
+    {
+        varying_scalars.iter().map(|vec| vec.len()).collect()
+    } else if let Some((_, varying_vectors)) = varying_vector_values.iter().next() {
+        varying_vectors.iter().map(|vec| vec.len()).collect()
+    } else {
+        Vec::new()
+    };
 
-            (
-                number_of_field_line_elements,
-                start_indices_of_field_line_elements,
-            )
-        };
+    let number_of_field_line_elements: usize = field_line_lengths.iter().sum();
 
     let mut fixed_scalar_names = Vec::new();
     let mut flat_fixed_scalar_values = Vec::new();
@@ -716,40 +865,12 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
             }
         };
 
-    let mut varying_scalar_names = Vec::new();
-    let mut flat_varying_scalar_values = Vec::new();
-
-    let set_varying_scalar_variables =
-        |varying_scalar_names: &mut Vec<_>, flat_varying_scalar_values: &mut Vec<_>| {
-            varying_scalar_names.reserve_exact(number_of_varying_scalar_quantities);
-            flat_varying_scalar_values
-                .reserve_exact(number_of_varying_scalar_quantities * number_of_field_line_elements);
-            for (name, values) in varying_scalar_values {
-                varying_scalar_names.push(name);
-                for vec in values {
-                    flat_varying_scalar_values.extend(vec.into_iter());
-                }
-            }
-        };
-
-    let mut varying_vector_names = Vec::new();
-    let mut flat_varying_vector_values = Vec::new();
-
-    let set_varying_vector_variables =
-        |varying_vector_names: &mut Vec<_>, flat_varying_vector_values: &mut Vec<ftr>| {
-            varying_vector_names.reserve_exact(number_of_varying_vector_quantities);
-            flat_varying_vector_values.reserve_exact(
-                number_of_varying_vector_quantities * number_of_field_line_elements * 3,
-            );
-            for (name, values) in varying_vector_values {
-                varying_vector_names.push(name);
-                for vec in values {
-                    for vec3 in vec {
-                        flat_varying_vector_values.extend(vec3.into_iter());
-                    }
-                }
-            }
-        };
+    // Varying quantities are written directly from `varying_scalar_values`/
+    // `varying_vector_values` further down (one `quantized::write_varying_quantity` call per
+    // quantity/component), so only their names are needed up front; collecting them here
+    // borrows rather than consumes the maps, leaving them intact for that later pass.
+    let varying_scalar_names: Vec<String> = varying_scalar_values.keys().cloned().collect();
+    let varying_vector_names: Vec<String> = varying_vector_values.keys().cloned().collect();
 
     rayon::scope(|s| {
         s.spawn(|_| {
@@ -762,22 +883,6 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
                 set_fixed_vector_variables(&mut fixed_vector_names, &mut flat_fixed_vector_values);
             }
         });
-        s.spawn(|_| {
-            if number_of_varying_scalar_quantities > 0 {
-                set_varying_scalar_variables(
-                    &mut varying_scalar_names,
-                    &mut flat_varying_scalar_values,
-                );
-            }
-        });
-        s.spawn(|_| {
-            if number_of_varying_vector_quantities > 0 {
-                set_varying_vector_variables(
-                    &mut varying_vector_names,
-                    &mut flat_varying_vector_values,
-                );
-            }
-        });
     });
 
     let mut names = Vec::with_capacity(
@@ -798,14 +903,11 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
     let float_size = mem::size_of::<ftr>();
 
     let section_sizes = [
-        7 * u64_size,
+        9 * u64_size,
         6 * float_size,
         names.len() * u8_size,
-        number_of_field_lines * u64_size,
         number_of_fixed_scalar_quantities * number_of_field_lines * float_size,
         number_of_fixed_vector_quantities * number_of_field_lines * 3 * float_size,
-        number_of_varying_scalar_quantities * number_of_field_line_elements * float_size,
-        number_of_varying_vector_quantities * number_of_field_line_elements * 3 * float_size,
     ];
 
     let byte_buffer_size = *section_sizes.iter().max().unwrap();
@@ -813,6 +915,7 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
 
     let byte_offset = utils::write_into_byte_buffer(
         &[
+            FORMAT_VERSION,
             float_size as u64,
             number_of_field_lines as u64,
             number_of_field_line_elements as u64,
@@ -820,6 +923,7 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
             number_of_fixed_vector_quantities as u64,
             number_of_varying_scalar_quantities as u64,
             number_of_varying_vector_quantities as u64,
+            compression_mode.tag() as u64,
         ],
         &mut byte_buffer,
         0,
@@ -844,15 +948,10 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
 
     write!(writer, "{}", names)?;
 
-    if number_of_field_line_elements > 0 {
-        let byte_offset = utils::write_into_byte_buffer(
-            &start_indices_of_field_line_elements,
-            &mut byte_buffer,
-            0,
-            ENDIANNESS,
-        );
-        mem::drop(start_indices_of_field_line_elements);
-        writer.write_all(&byte_buffer[..byte_offset])?;
+    if !field_line_lengths.is_empty() {
+        let inner_width = smallest_index_width_for(*field_line_lengths.iter().max().unwrap());
+        writer.write_all(&[inner_width])?;
+        write_packed_field_line_lengths(writer, &field_line_lengths, inner_width)?;
     }
 
     if number_of_fixed_scalar_quantities > 0 {
@@ -877,31 +976,262 @@ pub fn write_field_line_data_as_custom_binary<W: io::Write>(
         writer.write_all(&byte_buffer[..byte_offset])?;
     }
 
-    if number_of_varying_scalar_quantities > 0 {
-        let byte_offset = utils::write_into_byte_buffer(
-            &flat_varying_scalar_values,
-            &mut byte_buffer,
-            0,
-            ENDIANNESS,
-        );
-        mem::drop(flat_varying_scalar_values);
-        writer.write_all(&byte_buffer[..byte_offset])?;
+    for (_, values) in varying_scalar_values {
+        let mut flat_values = Vec::with_capacity(number_of_field_line_elements);
+        for vec in values {
+            flat_values.extend(vec.into_iter());
+        }
+        quantized::write_varying_quantity(writer, &flat_values, compression_mode)?;
     }
 
-    if number_of_varying_vector_quantities > 0 {
-        let byte_offset = utils::write_into_byte_buffer(
-            &flat_varying_vector_values,
-            &mut byte_buffer,
-            0,
-            ENDIANNESS,
-        );
-        mem::drop(flat_varying_vector_values);
-        writer.write_all(&byte_buffer[..byte_offset])?;
+    for (_, values) in varying_vector_values {
+        for component in [Dim3::X, Dim3::Y, Dim3::Z] {
+            let mut flat_component = Vec::with_capacity(number_of_field_line_elements);
+            for vec in &values {
+                for vec3 in vec {
+                    flat_component.push(vec3[component]);
+                }
+            }
+            quantized::write_varying_quantity(writer, &flat_component, compression_mode)?;
+        }
     }
 
     Ok(())
 }
 
+/// Writes the given field line data in a custom binary format into the given writer,
+/// packing and flushing each quantity `STREAMING_BATCH_SIZE` field lines at a time through a
+/// single reusable buffer instead of flattening the whole set into memory first. Peak memory
+/// stays bounded by the batch size regardless of `number_of_field_lines`, at the cost of the
+/// rayon-parallel flattening available in
+/// [`write_field_line_data_as_custom_binary_in_memory`].
+///
+/// This bound only holds for [`quantized::VaryingDataCompressionMode::Raw`]: zstd and
+/// quantized-zstd compression need the whole quantity in hand to compress it, so varying
+/// sections written under those modes are still buffered one quantity at a time.
+fn write_field_line_data_as_custom_binary_streaming<W: io::Write>(
+    writer: &mut W,
+    lower_bounds: &Vec3<ftr>,
+    upper_bounds: &Vec3<ftr>,
+    properties: FieldLineSetProperties3,
+    compression_mode: quantized::VaryingDataCompressionMode,
+) -> io::Result<()> {
+    const ENDIANNESS: Endianness = Endianness::Little;
+    const FORMAT_VERSION: u64 = 3;
+    const STREAMING_BATCH_SIZE: usize = 4096;
+
+    let FieldLineSetProperties3 {
+        number_of_field_lines,
+        fixed_scalar_values,
+        fixed_vector_values,
+        varying_scalar_values,
+        varying_vector_values,
+    } = properties;
+
+    let number_of_fixed_scalar_quantities = fixed_scalar_values.len();
+    let number_of_fixed_vector_quantities = fixed_vector_values.len();
+    let number_of_varying_scalar_quantities = varying_scalar_values.len();
+    let number_of_varying_vector_quantities = varying_vector_values.len();
+
+    let field_line_lengths: Vec<usize> = if let Some((_, varying_scalars)) =
+        varying_scalar_values.iter().next()
+    {
+        varying_scalars.iter().map(|vec| vec.len()).collect()
+    } else if let Some((_, varying_vectors)) = varying_vector_values.iter().next() {
+        varying_vectors.iter().map(|vec| vec.len()).collect()
+    } else {
+        Vec::new()
+    };
+    let number_of_field_line_elements: usize = field_line_lengths.iter().sum();
+
+    let mut names = Vec::with_capacity(
+        number_of_fixed_scalar_quantities
+            + number_of_fixed_vector_quantities
+            + number_of_varying_scalar_quantities
+            + number_of_varying_vector_quantities,
+    );
+    names.extend(fixed_scalar_values.keys().cloned());
+    names.extend(fixed_vector_values.keys().cloned());
+    names.extend(varying_scalar_values.keys().cloned());
+    names.extend(varying_vector_values.keys().cloned());
+    let mut names = names.join("\n");
+    names.push('\n');
+
+    let u64_size = mem::size_of::<u64>();
+    let float_size = mem::size_of::<ftr>();
+
+    let mut byte_buffer = vec![0_u8; STREAMING_BATCH_SIZE * 3 * float_size.max(u64_size)];
+
+    let byte_offset = utils::write_into_byte_buffer(
+        &[
+            FORMAT_VERSION,
+            float_size as u64,
+            number_of_field_lines as u64,
+            number_of_field_line_elements as u64,
+            number_of_fixed_scalar_quantities as u64,
+            number_of_fixed_vector_quantities as u64,
+            number_of_varying_scalar_quantities as u64,
+            number_of_varying_vector_quantities as u64,
+            compression_mode.tag() as u64,
+        ],
+        &mut byte_buffer,
+        0,
+        ENDIANNESS,
+    );
+    writer.write_all(&byte_buffer[..byte_offset])?;
+
+    let byte_offset = utils::write_into_byte_buffer(
+        &[
+            lower_bounds[Dim3::X],
+            upper_bounds[Dim3::X],
+            lower_bounds[Dim3::Y],
+            upper_bounds[Dim3::Y],
+            lower_bounds[Dim3::Z],
+            upper_bounds[Dim3::Z],
+        ],
+        &mut byte_buffer,
+        0,
+        ENDIANNESS,
+    );
+    writer.write_all(&byte_buffer[..byte_offset])?;
+
+    write!(writer, "{}", names)?;
+
+    if !field_line_lengths.is_empty() {
+        let inner_width = smallest_index_width_for(*field_line_lengths.iter().max().unwrap());
+        writer.write_all(&[inner_width])?;
+        write_packed_field_line_lengths(writer, &field_line_lengths, inner_width)?;
+    }
+
+    for (_, values) in fixed_scalar_values {
+        for batch in values.chunks(STREAMING_BATCH_SIZE) {
+            let byte_offset = utils::write_into_byte_buffer(batch, &mut byte_buffer, 0, ENDIANNESS);
+            writer.write_all(&byte_buffer[..byte_offset])?;
+        }
+    }
+
+    let mut component_batch = Vec::with_capacity(STREAMING_BATCH_SIZE * 3);
+    for (_, values) in fixed_vector_values {
+        for batch in values.chunks(STREAMING_BATCH_SIZE) {
+            component_batch.clear();
+            for vector in batch {
+                component_batch.push(vector[Dim3::X]);
+                component_batch.push(vector[Dim3::Y]);
+                component_batch.push(vector[Dim3::Z]);
+            }
+            let byte_offset =
+                utils::write_into_byte_buffer(&component_batch, &mut byte_buffer, 0, ENDIANNESS);
+            writer.write_all(&byte_buffer[..byte_offset])?;
+        }
+    }
+
+    for (_, values) in varying_scalar_values {
+        match compression_mode {
+            quantized::VaryingDataCompressionMode::Raw => {
+                writer.write_all(&[compression_mode.tag()])?;
+                for path in &values {
+                    for batch in path.chunks(STREAMING_BATCH_SIZE) {
+                        let byte_offset =
+                            utils::write_into_byte_buffer(batch, &mut byte_buffer, 0, ENDIANNESS);
+                        writer.write_all(&byte_buffer[..byte_offset])?;
+                    }
+                }
+            }
+            _ => {
+                let mut flat_values = Vec::with_capacity(number_of_field_line_elements);
+                for path in values {
+                    flat_values.extend(path.into_iter());
+                }
+                quantized::write_varying_quantity(writer, &flat_values, compression_mode)?;
+            }
+        }
+    }
+
+    for (_, values) in varying_vector_values {
+        match compression_mode {
+            quantized::VaryingDataCompressionMode::Raw => {
+                for component in [Dim3::X, Dim3::Y, Dim3::Z] {
+                    writer.write_all(&[compression_mode.tag()])?;
+                    for path in &values {
+                        for batch in path.chunks(STREAMING_BATCH_SIZE) {
+                            component_batch.clear();
+                            for vector in batch {
+                                component_batch.push(vector[component]);
+                            }
+                            let byte_offset = utils::write_into_byte_buffer(
+                                &component_batch,
+                                &mut byte_buffer,
+                                0,
+                                ENDIANNESS,
+                            );
+                            writer.write_all(&byte_buffer[..byte_offset])?;
+                        }
+                    }
+                }
+            }
+            _ => {
+                for component in [Dim3::X, Dim3::Y, Dim3::Z] {
+                    let mut flat_component = Vec::with_capacity(number_of_field_line_elements);
+                    for path in &values {
+                        for vec3 in path {
+                            flat_component.push(vec3[component]);
+                        }
+                    }
+                    quantized::write_varying_quantity(writer, &flat_component, compression_mode)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Picks the smallest integer byte width (1, 2, 4 or 8) whose unsigned range covers
+/// `max_value`, for packing the field line length index as compactly as possible.
+fn smallest_index_width_for(max_value: usize) -> u8 {
+    if max_value <= u8::MAX as usize {
+        1
+    } else if max_value <= u16::MAX as usize {
+        2
+    } else if max_value <= u32::MAX as usize {
+        4
+    } else {
+        8
+    }
+}
+
+/// Writes `lengths` packed at `inner_width` bytes per value, little-endian. The cumulative
+/// offsets (`start_indices_of_field_line_elements`) are recovered from this on read by a
+/// single prefix-sum pass, rather than storing them outright.
+fn write_packed_field_line_lengths<W: io::Write>(
+    writer: &mut W,
+    lengths: &[usize],
+    inner_width: u8,
+) -> io::Result<()> {
+    match inner_width {
+        1 => writer.write_all(&lengths.iter().map(|&length| length as u8).collect::<Vec<_>>()),
+        2 => {
+            for &length in lengths {
+                writer.write_all(&(length as u16).to_le_bytes())?;
+            }
+            Ok(())
+        }
+        4 => {
+            for &length in lengths {
+                writer.write_all(&(length as u32).to_le_bytes())?;
+            }
+            Ok(())
+        }
+        8 => {
+            for &length in lengths {
+                writer.write_all(&(length as u64).to_le_bytes())?;
+            }
+            Ok(())
+        }
+        _ => unreachable!("inner_width is always 1, 2, 4 or 8"),
+    }
+}
+
 /// Saves the given field line data as a H5Part file at the given path.
 #[cfg(feature = "hdf5")]
 pub fn save_field_line_data_as_h5part<P: AsRef<Path>>(
@@ -989,3 +1319,405 @@ pub fn save_field_line_data_as_h5part<P: AsRef<Path>>(
 
     Ok(())
 }
+
+/// Writes the given field line data as an Arrow IPC file into the given writer.
+///
+/// Fixed scalar quantities and the components of fixed vector quantities become
+/// `Float64` columns (one row per field line). Varying scalar and vector quantities
+/// become `List<Float64>` columns (one list element per field line, matching that
+/// line's path length). The domain bounds and field line count are attached as
+/// schema-level key/value metadata rather than columns, since they describe the
+/// whole set rather than varying per row.
+#[cfg(feature = "arrow")]
+pub fn write_field_line_data_as_arrow_ipc<W: io::Write>(
+    writer: &mut W,
+    lower_bounds: &Vec3<ftr>,
+    upper_bounds: &Vec3<ftr>,
+    properties: &FieldLineSetProperties3,
+) -> io::Result<()> {
+    use arrow::ipc::writer::FileWriter;
+
+    let (schema, batch) = build_field_line_record_batch(lower_bounds, upper_bounds, properties)?;
+
+    let mut ipc_writer = io_result!(FileWriter::try_new(writer, &schema))?;
+    io_result!(ipc_writer.write(&batch))?;
+    io_result!(ipc_writer.finish())?;
+
+    Ok(())
+}
+
+/// Builds an Arrow `RecordBatch` (and its schema) from the given field line data, using the
+/// same fixed/varying-quantity-to-column mapping as
+/// [`write_field_line_data_as_arrow_ipc`]. This is the shared construction path behind both
+/// that IPC writer and [`FieldLineSet3::to_record_batch`], so the two never drift apart.
+#[cfg(feature = "arrow")]
+fn build_field_line_record_batch(
+    lower_bounds: &Vec3<ftr>,
+    upper_bounds: &Vec3<ftr>,
+    properties: &FieldLineSetProperties3,
+) -> io::Result<(arrow::datatypes::SchemaRef, arrow::record_batch::RecordBatch)> {
+    use arrow::array::{ArrayRef, Float64Array, Float64Builder, ListBuilder};
+    use arrow::datatypes::{DataType, Field, Schema};
+    use arrow::record_batch::RecordBatch;
+    use std::sync::Arc;
+
+    let FieldLineSetProperties3 {
+        number_of_field_lines,
+        fixed_scalar_values,
+        fixed_vector_values,
+        varying_scalar_values,
+        varying_vector_values,
+    } = properties;
+    let number_of_field_lines = *number_of_field_lines;
+
+    let mut fields = Vec::new();
+    let mut columns: Vec<ArrayRef> = Vec::new();
+
+    for (name, values) in fixed_scalar_values {
+        fields.push(Field::new(name, DataType::Float64, false));
+        columns.push(Arc::new(Float64Array::from(values.clone())));
+    }
+
+    for (name, values) in fixed_vector_values {
+        for (component_name, component) in ["x", "y", "z"].iter().zip([Dim3::X, Dim3::Y, Dim3::Z]) {
+            fields.push(Field::new(
+                format!("{}_{}", name, component_name),
+                DataType::Float64,
+                false,
+            ));
+            columns.push(Arc::new(Float64Array::from(
+                values.iter().map(|vector| vector[component]).collect::<Vec<_>>(),
+            )));
+        }
+    }
+
+    for (name, values) in varying_scalar_values {
+        fields.push(Field::new(
+            name,
+            DataType::List(Box::new(Field::new("item", DataType::Float64, true))),
+            false,
+        ));
+        let mut builder = ListBuilder::new(Float64Builder::new());
+        for path_values in values {
+            builder.values().append_slice(path_values);
+            builder.append(true);
+        }
+        columns.push(Arc::new(builder.finish()));
+    }
+
+    for (name, values) in varying_vector_values {
+        for (component_name, component) in ["x", "y", "z"].iter().zip([Dim3::X, Dim3::Y, Dim3::Z]) {
+            fields.push(Field::new(
+                format!("{}_{}", name, component_name),
+                DataType::List(Box::new(Field::new("item", DataType::Float64, true))),
+                false,
+            ));
+            let mut builder = ListBuilder::new(Float64Builder::new());
+            for path_values in values {
+                builder
+                    .values()
+                    .append_slice(&path_values.iter().map(|vector| vector[component]).collect::<Vec<_>>());
+                builder.append(true);
+            }
+            columns.push(Arc::new(builder.finish()));
+        }
+    }
+
+    let metadata = [
+        ("number_of_field_lines".to_string(), number_of_field_lines.to_string()),
+        ("lower_bounds_x".to_string(), lower_bounds[Dim3::X].to_string()),
+        ("lower_bounds_y".to_string(), lower_bounds[Dim3::Y].to_string()),
+        ("lower_bounds_z".to_string(), lower_bounds[Dim3::Z].to_string()),
+        ("upper_bounds_x".to_string(), upper_bounds[Dim3::X].to_string()),
+        ("upper_bounds_y".to_string(), upper_bounds[Dim3::Y].to_string()),
+        ("upper_bounds_z".to_string(), upper_bounds[Dim3::Z].to_string()),
+    ]
+    .into_iter()
+    .collect();
+
+    let schema = Arc::new(Schema::new(fields).with_metadata(metadata));
+    let batch = io_result!(RecordBatch::try_new(Arc::clone(&schema), columns))?;
+
+    Ok((schema, batch))
+}
+
+/// Writes the given field line data as a quantized, bit-packed variant of the custom
+/// binary format into the given writer.
+///
+/// Layout:
+/// ```text
+/// number_of_field_lines:        u64
+/// number_of_field_line_elements_per_line: [u64; number_of_field_lines]
+/// number_of_fixed_scalar_quantities:   u64
+/// number_of_fixed_vector_quantities:   u64
+/// number_of_varying_scalar_quantities: u64
+/// number_of_varying_vector_quantities: u64
+/// for each quantity, in the order listed above:
+///     name_length: u64, name: [u8; name_length]
+///     quantized header and bit-packed payload (see `quantized::QuantizedValues::write`)
+/// ```
+/// The varying quantities named `x`, `y` and `z` (the field line path coordinates) are
+/// delta-encoded along each path before quantization, since consecutive points along a
+/// field line are close together.
+pub fn write_field_line_data_as_quantized_custom_binary<W: io::Write>(
+    writer: &mut W,
+    properties: &FieldLineSetProperties3,
+    bit_width: quantized::BitWidth,
+) -> io::Result<()> {
+    use quantized::{delta_encode, QuantizedValues};
+
+    let FieldLineSetProperties3 {
+        number_of_field_lines,
+        fixed_scalar_values,
+        fixed_vector_values,
+        varying_scalar_values,
+        varying_vector_values,
+    } = properties;
+
+    let path_lengths: Vec<u64> = if let Some((_, values)) = varying_scalar_values.iter().next() {
+        values.iter().map(|path| path.len() as u64).collect()
+    } else if let Some((_, values)) = varying_vector_values.iter().next() {
+        values.iter().map(|path| path.len() as u64).collect()
+    } else {
+        Vec::new()
+    };
+
+    writer.write_all(&(*number_of_field_lines as u64).to_le_bytes())?;
+    for &path_length in &path_lengths {
+        writer.write_all(&path_length.to_le_bytes())?;
+    }
+
+    writer.write_all(&(fixed_scalar_values.len() as u64).to_le_bytes())?;
+    writer.write_all(&(fixed_vector_values.len() as u64).to_le_bytes())?;
+    writer.write_all(&(varying_scalar_values.len() as u64).to_le_bytes())?;
+    writer.write_all(&(varying_vector_values.len() as u64).to_le_bytes())?;
+
+    let write_named_quantity = |writer: &mut W, name: &str, values: &[ftr]| -> io::Result<()> {
+        writer.write_all(&(name.len() as u64).to_le_bytes())?;
+        writer.write_all(name.as_bytes())?;
+        QuantizedValues::quantize(values, bit_width).write(writer)
+    };
+
+    for (name, values) in fixed_scalar_values {
+        write_named_quantity(writer, name, values)?;
+    }
+
+    for (name, values) in fixed_vector_values {
+        for (component_name, component) in ["x", "y", "z"].iter().zip([Dim3::X, Dim3::Y, Dim3::Z]) {
+            let component_values: Vec<ftr> = values.iter().map(|vector| vector[component]).collect();
+            write_named_quantity(writer, &format!("{}_{}", name, component_name), &component_values)?;
+        }
+    }
+
+    for (name, values) in varying_scalar_values {
+        let is_position_component = name == "x" || name == "y" || name == "z";
+        let flattened: Vec<ftr> = values
+            .iter()
+            .flat_map(|path| {
+                if is_position_component {
+                    delta_encode(path)
+                } else {
+                    path.clone()
+                }
+            })
+            .collect();
+        write_named_quantity(writer, name, &flattened)?;
+    }
+
+    for (name, values) in varying_vector_values {
+        for (component_name, component) in ["x", "y", "z"].iter().zip([Dim3::X, Dim3::Y, Dim3::Z]) {
+            let flattened: Vec<ftr> = values
+                .iter()
+                .flat_map(|path| path.iter().map(|vector| vector[component]))
+                .collect();
+            write_named_quantity(writer, &format!("{}_{}", name, component_name), &flattened)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the given field line data as an Apache Parquet file at the given path,
+/// writing `row_group_size` field lines per row group.
+#[cfg(feature = "parquet")]
+pub fn save_field_line_data_as_parquet<P: AsRef<Path>>(
+    output_file_path: P,
+    properties: FieldLineSetProperties3,
+    row_group_size: usize,
+    compression: parquet::basic::Compression,
+) -> io::Result<()> {
+    let file = utils::create_file_and_required_directories(output_file_path)?;
+    write_field_line_data_as_parquet(file, properties, row_group_size, compression)
+}
+
+/// Writes the given field line data as an Apache Parquet file into the given writer.
+///
+/// The set is flattened into a single table with one row per field-line element: each
+/// fixed scalar quantity becomes its own `DOUBLE` column broadcast across every element of
+/// its field line, each fixed/varying vector quantity becomes three `_x`/`_y`/`_z` component
+/// columns, each varying scalar quantity becomes its own column, and `field_line_start_index`
+/// records the first row belonging to each field line (so boundaries can be recovered without
+/// a `GROUP BY`). Rows are written `row_group_size` field lines per row group; all columns
+/// for the whole set are flattened in memory before the first row group is written.
+#[cfg(feature = "parquet")]
+pub fn write_field_line_data_as_parquet<W: io::Write + Send>(
+    writer: W,
+    properties: FieldLineSetProperties3,
+    row_group_size: usize,
+    compression: parquet::basic::Compression,
+) -> io::Result<()> {
+    use parquet::basic::Type as PhysicalType;
+    use parquet::column::writer::ColumnWriter;
+    use parquet::file::properties::WriterProperties;
+    use parquet::file::writer::{FileWriter, SerializedFileWriter};
+    use parquet::schema::types::Type;
+    use std::sync::Arc;
+
+    let FieldLineSetProperties3 {
+        fixed_scalar_values,
+        fixed_vector_values,
+        varying_scalar_values,
+        varying_vector_values,
+        ..
+    } = properties;
+
+    let path_lengths: Vec<usize> = if let Some((_, values)) = varying_scalar_values.iter().next() {
+        values.iter().map(|path| path.len()).collect()
+    } else if let Some((_, values)) = varying_vector_values.iter().next() {
+        values.iter().map(|path| path.len()).collect()
+    } else {
+        fixed_scalar_values
+            .iter()
+            .next()
+            .map(|(_, values)| vec![1; values.len()])
+            .or_else(|| {
+                fixed_vector_values
+                    .iter()
+                    .next()
+                    .map(|(_, values)| vec![1; values.len()])
+            })
+            .unwrap_or_default()
+    };
+
+    let start_indices: Vec<i64> = path_lengths
+        .iter()
+        .scan(0i64, |offset, &length| {
+            let start = *offset;
+            *offset += length as i64;
+            Some(start)
+        })
+        .collect();
+
+    let mut column_names = vec!["field_line_start_index".to_string()];
+    let mut columns: Vec<Vec<f64>> = vec![start_indices
+        .iter()
+        .zip(&path_lengths)
+        .flat_map(|(&start, &length)| std::iter::repeat(start as f64).take(length.max(1)))
+        .collect()];
+
+    for (name, values) in &fixed_scalar_values {
+        column_names.push(name.clone());
+        columns.push(
+            values
+                .iter()
+                .zip(&path_lengths)
+                .flat_map(|(&value, &length)| std::iter::repeat(value as f64).take(length.max(1)))
+                .collect(),
+        );
+    }
+    for (name, values) in &fixed_vector_values {
+        for (component_name, component) in ["x", "y", "z"].iter().zip([Dim3::X, Dim3::Y, Dim3::Z]) {
+            column_names.push(format!("{}_{}", name, component_name));
+            columns.push(
+                values
+                    .iter()
+                    .zip(&path_lengths)
+                    .flat_map(|(vector, &length)| {
+                        std::iter::repeat(vector[component] as f64).take(length.max(1))
+                    })
+                    .collect(),
+            );
+        }
+    }
+    for (name, values) in &varying_scalar_values {
+        column_names.push(name.clone());
+        columns.push(
+            values
+                .iter()
+                .flat_map(|path| path.iter().map(|&value| value as f64))
+                .collect(),
+        );
+    }
+    for (name, values) in &varying_vector_values {
+        for (component_name, component) in ["x", "y", "z"].iter().zip([Dim3::X, Dim3::Y, Dim3::Z]) {
+            column_names.push(format!("{}_{}", name, component_name));
+            columns.push(
+                values
+                    .iter()
+                    .flat_map(|path| path.iter().map(|vector| vector[component] as f64))
+                    .collect(),
+            );
+        }
+    }
+
+    let schema_fields: Vec<Arc<Type>> = column_names
+        .iter()
+        .map(|name| {
+            Arc::new(
+                Type::primitive_type_builder(name, PhysicalType::DOUBLE)
+                    .build()
+                    .unwrap(),
+            )
+        })
+        .collect();
+    let schema = Arc::new(
+        Type::group_type_builder("field_line_data")
+            .with_fields(&mut schema_fields.clone())
+            .build()
+            .unwrap(),
+    );
+
+    let writer_properties = Arc::new(
+        WriterProperties::builder()
+            .set_compression(compression)
+            .build(),
+    );
+
+    let mut file_writer =
+        io_result!(SerializedFileWriter::new(writer, schema, writer_properties))?;
+
+    let number_of_rows = columns.first().map_or(0, Vec::len);
+    let row_group_boundaries: Vec<usize> = path_lengths
+        .chunks(row_group_size.max(1))
+        .scan(0, |row_offset, lengths| {
+            let start = *row_offset;
+            *row_offset += lengths.iter().map(|&length| length.max(1)).sum::<usize>();
+            Some((start, *row_offset))
+        })
+        .collect();
+
+    for (row_start, row_end) in if number_of_rows == 0 {
+        Vec::new()
+    } else {
+        row_group_boundaries
+    } {
+        let mut row_group_writer = io_result!(file_writer.next_row_group())?;
+        let mut column_idx = 0;
+        while let Some(mut column_writer) = io_result!(row_group_writer.next_column())? {
+            let values = &columns[column_idx][row_start..row_end];
+            match column_writer {
+                ColumnWriter::DoubleColumnWriter(ref mut typed_writer) => {
+                    io_result!(typed_writer.write_batch(values, None, None))?;
+                }
+                _ => unreachable!("all field line quantities are written as DOUBLE columns"),
+            }
+            io_result!(row_group_writer.close_column(column_writer))?;
+            column_idx += 1;
+        }
+        io_result!(file_writer.close_row_group(row_group_writer))?;
+    }
+
+    io_result!(file_writer.close())?;
+
+    Ok(())
+}
@@ -0,0 +1,235 @@
+//! Quantized, bit-packed variant of the custom binary field line format.
+//!
+//! Every fixed/varying quantity is quantized independently: each value's range
+//! is scanned for `min`/`max`, then mapped to an unsigned integer of a
+//! user-chosen bit width `B` via `round((v - min) / (max - min) * (2^B - 1))`
+//! and bit-packed contiguously. The header records `min`, `max` and the bit
+//! width actually used for packing per quantity, which can be smaller than `B`
+//! after the divisor reduction described below; since `max` always maps to the
+//! top code `2^B - 1`, a decoder recovers that code as the largest stored code
+//! times the divisor and can invert the mapping exactly (up to the requested
+//! precision). Varying position arrays are first delta-encoded along each
+//! path before quantization, since consecutive points along a field line are
+//! close together, and the quantized integers of each column are optionally
+//! divided by their greatest common divisor (with the divisor stored) to
+//! shave off further bits.
+
+use super::ftr;
+use std::io::{self, Write};
+
+/// Bit width used to quantize a single fixed/varying quantity.
+pub type BitWidth = u8;
+
+/// Quantization parameters and bit-packed payload for a single quantity.
+#[derive(Clone, Debug)]
+pub struct QuantizedValues {
+    min: ftr,
+    max: ftr,
+    bit_width: BitWidth,
+    divisor: u64,
+    count: usize,
+    packed_bits: Vec<u8>,
+}
+
+impl QuantizedValues {
+    /// Quantizes `values` to the given bit width, storing the result bit-packed.
+    pub fn quantize(values: &[ftr], bit_width: BitWidth) -> Self {
+        let min = values.iter().cloned().fold(ftr::INFINITY, ftr::min);
+        let max = values.iter().cloned().fold(ftr::NEG_INFINITY, ftr::max);
+        let range = max - min;
+        let levels = 1u64.checked_shl(bit_width as u32).unwrap_or(0).wrapping_sub(1) as ftr;
+
+        let quantized: Vec<u64> = values
+            .iter()
+            .map(|&value| {
+                if range > 0.0 {
+                    (((value - min) / range) * levels).round() as u64
+                } else {
+                    0
+                }
+            })
+            .collect();
+
+        let divisor = gcd_of_all(&quantized).max(1);
+        let divided: Vec<u64> = if divisor > 1 {
+            quantized.iter().map(|&value| value / divisor).collect()
+        } else {
+            quantized
+        };
+
+        // Dividing by `divisor` shrinks every code, but packing at the original `bit_width`
+        // would keep spending the same number of bits per value regardless. Recompute the
+        // width from the reduced values' actual maximum so the GCD reduction also shrinks
+        // the packed payload, not just the codes within it.
+        let packed_bit_width = bits_needed_for_max_value(divided.iter().cloned().max().unwrap_or(0));
+
+        Self {
+            min,
+            max,
+            bit_width: packed_bit_width,
+            divisor,
+            count: values.len(),
+            packed_bits: pack_bits(&divided, packed_bit_width),
+        }
+    }
+
+    /// Writes this quantity's header (`min`, `max`, `bit_width`, `divisor`, element count
+    /// and packed byte count) followed by its bit-packed payload.
+    pub fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&(self.min as f64).to_le_bytes())?;
+        writer.write_all(&(self.max as f64).to_le_bytes())?;
+        writer.write_all(&[self.bit_width])?;
+        writer.write_all(&self.divisor.to_le_bytes())?;
+        writer.write_all(&(self.count as u64).to_le_bytes())?;
+        writer.write_all(&(self.packed_bits.len() as u64).to_le_bytes())?;
+        writer.write_all(&self.packed_bits)
+    }
+
+    /// Writes this quantity the same way as [`write`](Self::write), but zstd-compresses the
+    /// bit-packed payload first, storing the compressed byte count in place of the raw one.
+    pub fn write_zstd_compressed<W: Write>(&self, writer: &mut W, zstd_level: i32) -> io::Result<()> {
+        let compressed = zstd::encode_all(&self.packed_bits[..], zstd_level)?;
+        writer.write_all(&(self.min as f64).to_le_bytes())?;
+        writer.write_all(&(self.max as f64).to_le_bytes())?;
+        writer.write_all(&[self.bit_width])?;
+        writer.write_all(&self.divisor.to_le_bytes())?;
+        writer.write_all(&(self.count as u64).to_le_bytes())?;
+        writer.write_all(&(compressed.len() as u64).to_le_bytes())?;
+        writer.write_all(&compressed)
+    }
+}
+
+/// Compression strategy for the varying scalar/vector sections of the custom binary field
+/// line format, selected by the caller of
+/// [`write_field_line_data_as_custom_binary`](super::write_field_line_data_as_custom_binary)
+/// and recorded per-quantity (via the mode tag written by
+/// [`write_varying_quantity`]) so a reader can invert each section without guessing.
+#[derive(Clone, Copy, Debug)]
+pub enum VaryingDataCompressionMode {
+    /// Values are written as raw `ftr` floats.
+    Raw,
+    /// Values are written as raw `ftr` floats through a zstd stream.
+    Zstd { level: i32 },
+    /// Values are quantized to fixed-point codes giving (approximately) the requested
+    /// relative precision, then the resulting integer stream is zstd-compressed.
+    LossyQuantized {
+        relative_precision: f64,
+        zstd_level: i32,
+    },
+}
+
+impl VaryingDataCompressionMode {
+    pub(crate) fn tag(self) -> u8 {
+        match self {
+            Self::Raw => 0,
+            Self::Zstd { .. } => 1,
+            Self::LossyQuantized { .. } => 2,
+        }
+    }
+}
+
+/// Picks the bit width giving (approximately) the requested relative precision: the
+/// smallest `B` with `1/(2^B - 1) <= relative_precision`.
+pub fn bit_width_for_relative_precision(relative_precision: f64) -> BitWidth {
+    if relative_precision <= 0.0 {
+        return 64;
+    }
+    ((1.0 / relative_precision + 1.0).log2().ceil() as i64).clamp(1, 64) as BitWidth
+}
+
+/// Writes a single varying quantity's flattened values under the given compression mode: a
+/// one-byte mode tag, followed by the mode-specific payload.
+pub fn write_varying_quantity<W: Write>(
+    writer: &mut W,
+    values: &[ftr],
+    mode: VaryingDataCompressionMode,
+) -> io::Result<()> {
+    writer.write_all(&[mode.tag()])?;
+    match mode {
+        VaryingDataCompressionMode::Raw => {
+            for &value in values {
+                writer.write_all(&(value as f64).to_le_bytes())?;
+            }
+            Ok(())
+        }
+        VaryingDataCompressionMode::Zstd { level } => {
+            let mut encoder = zstd::Encoder::new(Vec::new(), level)?;
+            for &value in values {
+                encoder.write_all(&(value as f64).to_le_bytes())?;
+            }
+            let compressed = encoder.finish()?;
+            writer.write_all(&(compressed.len() as u64).to_le_bytes())?;
+            writer.write_all(&compressed)
+        }
+        VaryingDataCompressionMode::LossyQuantized {
+            relative_precision,
+            zstd_level,
+        } => {
+            let bit_width = bit_width_for_relative_precision(relative_precision);
+            QuantizedValues::quantize(values, bit_width).write_zstd_compressed(writer, zstd_level)
+        }
+    }
+}
+
+/// Delta-encodes `path` (the first point is kept as-is, every following point
+/// is replaced by its difference to the previous one), so that a decoder can
+/// reconstruct the original path with a running sum.
+pub fn delta_encode(path: &[ftr]) -> Vec<ftr> {
+    if path.is_empty() {
+        return Vec::new();
+    }
+    let mut deltas = Vec::with_capacity(path.len());
+    deltas.push(path[0]);
+    for window in path.windows(2) {
+        deltas.push(window[1] - window[0]);
+    }
+    deltas
+}
+
+/// Smallest number of bits `B` with `max_value <= 2^B - 1`, i.e. `ceil(log2(max_value + 1))`,
+/// clamped to at least 1 (so an all-zero column still packs, rather than a zero-width field).
+fn bits_needed_for_max_value(max_value: u64) -> BitWidth {
+    (64 - max_value.leading_zeros()).max(1) as BitWidth
+}
+
+fn gcd_of_all(values: &[u64]) -> u64 {
+    values
+        .iter()
+        .cloned()
+        .filter(|&value| value > 0)
+        .fold(0, gcd)
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Bit-packs `values` (each assumed to fit in `bit_width` bits) contiguously
+/// into a byte buffer, least-significant-bit first.
+fn pack_bits(values: &[u64], bit_width: BitWidth) -> Vec<u8> {
+    let mut bits = Vec::with_capacity((values.len() * bit_width as usize + 7) / 8);
+    // A `bit_width` up to 64 (reachable from `LossyQuantized` with a tiny `relative_precision`)
+    // plus up to 7 bits already waiting to be flushed can exceed 64 accumulated bits at once, so
+    // a `u64` accumulator would silently drop the overflowing high bits. `u128` keeps the widest
+    // value this format can produce (64 bits) plus that 7-bit carry comfortably within range.
+    let mut accumulator: u128 = 0;
+    let mut n_accumulated_bits = 0u32;
+
+    for &value in values {
+        accumulator |= u128::from(value) << n_accumulated_bits;
+        n_accumulated_bits += bit_width as u32;
+        while n_accumulated_bits >= 8 {
+            bits.push((accumulator & 0xFF) as u8);
+            accumulator >>= 8;
+            n_accumulated_bits -= 8;
+        }
+    }
+    if n_accumulated_bits > 0 {
+        bits.push((accumulator & 0xFF) as u8);
+    }
+    bits
+}
@@ -0,0 +1,268 @@
+//! Streaming trace-and-serialize path for very large seed sets.
+//!
+//! [`FieldLineSet3::trace`](super::FieldLineSet3::trace) collects every traced line into
+//! one in-memory [`FieldLineSetProperties3`] before anything is written, which can exhaust
+//! memory when tracing millions of seed points. [`trace_and_write_custom_binary`] instead
+//! consumes each traced field line as it is produced by
+//! [`super::super::batch::trace_batch_streaming`] and appends a self-contained per-line
+//! record to the writer immediately, deferring the global header (which needs per-line
+//! start offsets that aren't known until every line has been traced) to a trailer written
+//! after the body. A fixed-size footer at the very end of the file points to the trailer's
+//! offset, so a reader seeks to the footer first, then to the trailer, rather than
+//! requiring every per-line record to be buffered up front.
+//!
+//! # File layout
+//!
+//! ```text
+//! body:    one per-line record per traced field line, in seed order
+//! trailer: number_of_field_lines: u64
+//!          fixed_scalar_names, fixed_vector_names,
+//!          varying_scalar_names, varying_vector_names (each: u64 count, then per name:
+//!              u64 byte length followed by the name's UTF-8 bytes)
+//!          start_indices_of_field_line_elements: [u64; number_of_field_lines]
+//! footer:  trailer_offset: u64 (fixed size, always the last 8 bytes of the file)
+//! ```
+
+use super::{FieldLineSetProperties3, FieldLineTracer3};
+use crate::{
+    geometry::Dim3,
+    grid::Grid3,
+    interpolation::Interpolator3,
+    io::snapshot::{fdt, SnapshotCacher3, SnapshotProvider3},
+    seeding::Seeder3,
+    tracing::{
+        batch::{trace_batch_streaming, BatchTracerConfig, IndexedTraceResult, OrderedReassembler},
+        stepping::StepperFactory3,
+    },
+};
+use rayon::iter::FromParallelIterator;
+use std::io::{self, Write};
+
+/// Traces all the field lines produced by `seeder` and writes them to `writer` as they
+/// complete, without ever materializing the whole set in memory.
+///
+/// See the [module-level documentation](self) for the on-disk layout.
+///
+/// # Type parameters
+///
+/// - `Sd`: Type of seeder.
+/// - `Tr`: Type of field line tracer.
+/// - `G`: Type of grid.
+/// - `I`: Type of interpolator.
+/// - `StF`: Type of stepper factory.
+/// - `W`: Type of writer.
+pub fn trace_and_write_custom_binary<Sd, Tr, G, P, I, StF, W>(
+    field_name: &str,
+    snapshot: &SnapshotCacher3<G, P>,
+    seeder: Sd,
+    tracer: &Tr,
+    interpolator: &I,
+    stepper_factory: &StF,
+    config: &BatchTracerConfig,
+    writer: &mut W,
+) -> io::Result<()>
+where
+    Sd: Seeder3,
+    Tr: FieldLineTracer3 + Sync,
+    Tr::Data: Send,
+    FieldLineSetProperties3: FromParallelIterator<Tr::Data>,
+    G: Grid3<fdt>,
+    P: SnapshotProvider3<G> + Sync,
+    I: Interpolator3,
+    StF: StepperFactory3 + Sync,
+    W: Write + Send,
+{
+    let mut counting_writer = CountingWriter::new(writer);
+
+    let mut fixed_scalar_names: Option<Vec<String>> = None;
+    let mut fixed_vector_names: Option<Vec<String>> = None;
+    let mut varying_scalar_names: Option<Vec<String>> = None;
+    let mut varying_vector_names: Option<Vec<String>> = None;
+
+    let mut start_indices = Vec::new();
+    let mut next_start_index: u64 = 0;
+    let mut write_error: Option<io::Error> = None;
+
+    let mut reassembler = OrderedReassembler::new();
+
+    trace_batch_streaming(
+        field_name,
+        snapshot,
+        seeder,
+        tracer,
+        interpolator,
+        stepper_factory,
+        config,
+        |result: IndexedTraceResult<Tr::Data>| {
+            reassembler.accept(result, |data| {
+                if write_error.is_some() {
+                    return;
+                }
+
+                // A seed point that produced no trace (outside the domain, degenerate line)
+                // contributes no record: `start_indices`/`number_of_field_lines` in the
+                // trailer only count lines actually written below.
+                let data = match data {
+                    Some(data) => data,
+                    None => return,
+                };
+
+                let properties: FieldLineSetProperties3 = rayon::iter::once(data).collect();
+
+                let fixed_scalar_names = fixed_scalar_names
+                    .get_or_insert_with(|| properties.fixed_scalar_values.keys().cloned().collect());
+                let fixed_vector_names = fixed_vector_names
+                    .get_or_insert_with(|| properties.fixed_vector_values.keys().cloned().collect());
+                let varying_scalar_names = varying_scalar_names
+                    .get_or_insert_with(|| properties.varying_scalar_values.keys().cloned().collect());
+                let varying_vector_names = varying_vector_names
+                    .get_or_insert_with(|| properties.varying_vector_values.keys().cloned().collect());
+
+                match write_single_line_record(
+                    &mut counting_writer,
+                    &properties,
+                    fixed_scalar_names,
+                    fixed_vector_names,
+                    varying_scalar_names,
+                    varying_vector_names,
+                ) {
+                    Ok(n_elements) => {
+                        start_indices.push(next_start_index);
+                        next_start_index += n_elements as u64;
+                    }
+                    Err(err) => write_error = Some(err),
+                }
+            });
+        },
+        |_, _| {},
+    );
+
+    if let Some(err) = write_error {
+        return Err(err);
+    }
+
+    let trailer_offset = counting_writer.bytes_written();
+
+    write_trailer(
+        &mut counting_writer,
+        start_indices.len() as u64,
+        &fixed_scalar_names.unwrap_or_default(),
+        &fixed_vector_names.unwrap_or_default(),
+        &varying_scalar_names.unwrap_or_default(),
+        &varying_vector_names.unwrap_or_default(),
+        &start_indices,
+    )?;
+
+    counting_writer.write_all(&trailer_offset.to_le_bytes())?;
+
+    Ok(())
+}
+
+/// Writes a single field line's record: its fixed scalar and vector values (in the order
+/// given by the name lists) followed by its varying scalar and vector values.
+fn write_single_line_record<W: Write>(
+    writer: &mut W,
+    properties: &FieldLineSetProperties3,
+    fixed_scalar_names: &[String],
+    fixed_vector_names: &[String],
+    varying_scalar_names: &[String],
+    varying_vector_names: &[String],
+) -> io::Result<usize> {
+    let n_elements = varying_scalar_names
+        .first()
+        .and_then(|name| properties.varying_scalar_values.get(name))
+        .map(|values| values[0].len())
+        .or_else(|| {
+            varying_vector_names
+                .first()
+                .and_then(|name| properties.varying_vector_values.get(name))
+                .map(|values| values[0].len())
+        })
+        .unwrap_or(0);
+
+    for name in fixed_scalar_names {
+        let value = properties.fixed_scalar_values[name][0];
+        writer.write_all(&(value as f64).to_le_bytes())?;
+    }
+    for name in fixed_vector_names {
+        let vector = &properties.fixed_vector_values[name][0];
+        for component in [Dim3::X, Dim3::Y, Dim3::Z] {
+            writer.write_all(&(vector[component] as f64).to_le_bytes())?;
+        }
+    }
+    for name in varying_scalar_names {
+        for &value in &properties.varying_scalar_values[name][0] {
+            writer.write_all(&(value as f64).to_le_bytes())?;
+        }
+    }
+    for name in varying_vector_names {
+        for vector in &properties.varying_vector_values[name][0] {
+            for component in [Dim3::X, Dim3::Y, Dim3::Z] {
+                writer.write_all(&(vector[component] as f64).to_le_bytes())?;
+            }
+        }
+    }
+
+    Ok(n_elements)
+}
+
+fn write_trailer<W: Write>(
+    writer: &mut W,
+    number_of_field_lines: u64,
+    fixed_scalar_names: &[String],
+    fixed_vector_names: &[String],
+    varying_scalar_names: &[String],
+    varying_vector_names: &[String],
+    start_indices: &[u64],
+) -> io::Result<()> {
+    writer.write_all(&number_of_field_lines.to_le_bytes())?;
+    write_names(writer, fixed_scalar_names)?;
+    write_names(writer, fixed_vector_names)?;
+    write_names(writer, varying_scalar_names)?;
+    write_names(writer, varying_vector_names)?;
+    for &start_index in start_indices {
+        writer.write_all(&start_index.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn write_names<W: Write>(writer: &mut W, names: &[String]) -> io::Result<()> {
+    writer.write_all(&(names.len() as u64).to_le_bytes())?;
+    for name in names {
+        writer.write_all(&(name.len() as u64).to_le_bytes())?;
+        writer.write_all(name.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// A `Write` adapter counting the total number of bytes written through it, so a streaming
+/// writer can record byte offsets without requiring the underlying writer to be `Seek`.
+struct CountingWriter<'a, W: Write> {
+    inner: &'a mut W,
+    bytes_written: u64,
+}
+
+impl<'a, W: Write> CountingWriter<'a, W> {
+    fn new(inner: &'a mut W) -> Self {
+        Self {
+            inner,
+            bytes_written: 0,
+        }
+    }
+
+    fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+}
+
+impl<'a, W: Write> Write for CountingWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.bytes_written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,50 @@
+//! Loading [`RKFStepperConfig`](super::RKFStepperConfig) defaults from a YAML numerics file.
+//!
+//! Large simulation codes typically ship a single `numerics.yaml` holding all solver
+//! tolerances and iteration limits, so that a run can be reproduced and version-controlled
+//! without reconstructing a long command line. [`RKFStepperConfigFileSpec`] is the
+//! deserialization target for such a file: every field is optional, so the file only needs to
+//! specify the parameters it wants to override, and
+//! [`construct_rkf_stepper_config_from_options`](crate::cli::tracing::stepping::rkf::construct_rkf_stepper_config_from_options)
+//! falls back to the corresponding CLI flag (or its hardcoded default) for anything left out.
+//! Unknown keys are rejected, with [`serde_yaml`] reporting the offending key and its line and
+//! column in the error message.
+
+use crate::tracing::ftr;
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+/// Optional overrides for [`RKFStepperConfig`](super::RKFStepperConfig) fields, read from a YAML
+/// numerics file. A CLI flag given explicitly on the command line always takes precedence over
+/// the corresponding entry here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RKFStepperConfigFileSpec {
+    pub stepping_scheme: Option<String>,
+    pub dense_step_length: Option<ftr>,
+    pub max_step_attempts: Option<usize>,
+    pub absolute_tolerance: Option<ftr>,
+    pub relative_tolerance: Option<ftr>,
+    pub safety_factor: Option<ftr>,
+    pub min_step_scale: Option<ftr>,
+    pub max_step_scale: Option<ftr>,
+    pub initial_error: Option<ftr>,
+    pub initial_step_length: Option<ftr>,
+    pub sudden_reversals_for_sink: Option<u8>,
+}
+
+impl RKFStepperConfigFileSpec {
+    /// Reads and parses a YAML numerics file at the given path.
+    ///
+    /// Returns a human-readable error (including the offending key and line, for unknown
+    /// fields or malformed values) rather than panicking, since a malformed numerics file is a
+    /// user input error, not a programming error.
+    pub fn from_yaml_file<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Could not read stepper config file {}: {}", path.display(), err))?;
+        serde_yaml::from_str(&contents)
+            .map_err(|err| format!("Could not parse stepper config file {}: {}", path.display(), err))
+    }
+}
@@ -0,0 +1,215 @@
+//! Collection and summarization of per-step error diagnostics for RKF steppers.
+//!
+//! [`RKFStepper3::compute_error_deltas`](super::RKFStepper3::compute_error_deltas) computes
+//! an embedded error estimate on every step, but the step-size controller discards it once
+//! it has decided whether to accept or reject the step. Threading a [`DiagnosticsCollector3`]
+//! through the stepping loop retains that error stream and turns it into actionable tuning
+//! feedback: percentile summaries, a kernel density estimate of the error-magnitude
+//! distribution, and the arc-length intervals where the integrator struggled (produced
+//! errors beyond the upper Tukey fence on one or more consecutive steps).
+
+use crate::tracing::ftr;
+
+/// A single step's error diagnostics.
+#[derive(Clone, Copy, Debug)]
+pub struct StepDiagnostics3 {
+    /// Arc length at the end of the step.
+    pub distance: ftr,
+    /// Scaled error norm used by the step-size controller to accept or reject the step.
+    pub scaled_error_norm: ftr,
+    /// Step size used for this attempt.
+    pub step_size: ftr,
+    /// Whether the step-size controller accepted this attempt.
+    pub accepted: bool,
+}
+
+/// Collects [`StepDiagnostics3`] for every step attempted during a stepping run.
+#[derive(Clone, Debug, Default)]
+pub struct DiagnosticsCollector3 {
+    steps: Vec<StepDiagnostics3>,
+}
+
+impl DiagnosticsCollector3 {
+    /// Creates a new, empty collector.
+    pub fn new() -> Self {
+        Self { steps: Vec::new() }
+    }
+
+    /// Records the outcome of a single step attempt.
+    pub fn record_step(
+        &mut self,
+        distance: ftr,
+        scaled_error_norm: ftr,
+        step_size: ftr,
+        accepted: bool,
+    ) {
+        self.steps.push(StepDiagnostics3 {
+            distance,
+            scaled_error_norm,
+            step_size,
+            accepted,
+        });
+    }
+
+    /// Summarizes the recorded steps into percentiles, a kernel density estimate of the
+    /// error-magnitude distribution, and the flagged struggle regions.
+    ///
+    /// Returns `None` if no steps have been recorded.
+    pub fn summarize(&self) -> Option<DiagnosticsSummary3> {
+        if self.steps.is_empty() {
+            return None;
+        }
+
+        let mut sorted_errors: Vec<ftr> = self
+            .steps
+            .iter()
+            .map(|step| step.scaled_error_norm)
+            .collect();
+        sorted_errors.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let q1 = percentile(&sorted_errors, 25.0);
+        let median = percentile(&sorted_errors, 50.0);
+        let q3 = percentile(&sorted_errors, 75.0);
+        let upper_tukey_fence = q3 + 1.5 * (q3 - q1);
+
+        let kde = ErrorKde3::from_samples(&sorted_errors);
+        let struggle_regions = flag_struggle_regions(&self.steps, upper_tukey_fence);
+
+        Some(DiagnosticsSummary3 {
+            n_steps: self.steps.len(),
+            n_rejected: self.steps.iter().filter(|step| !step.accepted).count(),
+            q1,
+            median,
+            q3,
+            upper_tukey_fence,
+            kde,
+            struggle_regions,
+        })
+    }
+}
+
+/// Linearly interpolated percentile of an already-sorted sample, interpolating
+/// between the two nearest ranks.
+fn percentile(sorted_values: &[ftr], percentile: ftr) -> ftr {
+    if sorted_values.len() == 1 {
+        return sorted_values[0];
+    }
+    let rank = (percentile / 100.0) * ((sorted_values.len() - 1) as ftr);
+    let lower_index = rank.floor() as usize;
+    let upper_index = rank.ceil() as usize;
+    if lower_index == upper_index {
+        sorted_values[lower_index]
+    } else {
+        let fraction = rank - (lower_index as ftr);
+        sorted_values[lower_index] * (1.0 - fraction) + sorted_values[upper_index] * fraction
+    }
+}
+
+/// A Gaussian kernel density estimate of the error-magnitude distribution,
+/// using Silverman's rule of thumb for the bandwidth.
+#[derive(Clone, Debug)]
+pub struct ErrorKde3 {
+    samples: Vec<ftr>,
+    bandwidth: ftr,
+}
+
+impl ErrorKde3 {
+    /// Fits a Gaussian KDE to the given samples using Silverman's bandwidth rule,
+    /// `h = 1.06 * sigma * n^(-1/5)`.
+    fn from_samples(samples: &[ftr]) -> Self {
+        let n = samples.len() as ftr;
+        let mean = samples.iter().sum::<ftr>() / n;
+        let variance = samples.iter().map(|&value| (value - mean).powi(2)).sum::<ftr>() / n;
+        let std_dev = variance.sqrt();
+        let bandwidth = if std_dev > 0.0 {
+            1.06 * std_dev * n.powf(-1.0 / 5.0)
+        } else {
+            // A degenerate (zero-variance) sample has no meaningful bandwidth;
+            // fall back to a small constant so density evaluation still works.
+            1e-3
+        };
+        Self {
+            samples: samples.to_vec(),
+            bandwidth,
+        }
+    }
+
+    /// Evaluates the estimated probability density at the given point.
+    pub fn density_at(&self, x: ftr) -> ftr {
+        let n = self.samples.len() as ftr;
+        let normalization = (n * self.bandwidth * (2.0 * std::f64::consts::PI as ftr).sqrt()).recip();
+        let sum: ftr = self
+            .samples
+            .iter()
+            .map(|&sample| {
+                let z = (x - sample) / self.bandwidth;
+                (-0.5 * z * z).exp()
+            })
+            .sum();
+        normalization * sum
+    }
+}
+
+/// A contiguous arc-length interval of one or more consecutive steps whose
+/// scaled error norm exceeded the upper Tukey fence.
+#[derive(Clone, Copy, Debug)]
+pub struct StruggleRegion3 {
+    /// Arc length at the start of the flagged interval.
+    pub start_distance: ftr,
+    /// Arc length at the end of the flagged interval.
+    pub end_distance: ftr,
+    /// Number of consecutive flagged steps within the interval.
+    pub n_flagged_steps: usize,
+}
+
+/// Groups consecutive steps whose scaled error norm exceeds `upper_tukey_fence`
+/// into contiguous arc-length intervals.
+fn flag_struggle_regions(steps: &[StepDiagnostics3], upper_tukey_fence: ftr) -> Vec<StruggleRegion3> {
+    let mut regions = Vec::new();
+    let mut current: Option<(ftr, ftr, usize)> = None;
+
+    for step in steps {
+        if step.scaled_error_norm > upper_tukey_fence {
+            current = Some(match current {
+                Some((start, _, count)) => (start, step.distance, count + 1),
+                None => (step.distance, step.distance, 1),
+            });
+        } else if let Some((start, end, count)) = current.take() {
+            regions.push(StruggleRegion3 {
+                start_distance: start,
+                end_distance: end,
+                n_flagged_steps: count,
+            });
+        }
+    }
+    if let Some((start, end, count)) = current {
+        regions.push(StruggleRegion3 {
+            start_distance: start,
+            end_distance: end,
+            n_flagged_steps: count,
+        });
+    }
+
+    regions
+}
+
+/// Summary statistics produced by [`DiagnosticsCollector3::summarize`].
+#[derive(Clone, Debug)]
+pub struct DiagnosticsSummary3 {
+    /// Total number of step attempts recorded.
+    pub n_steps: usize,
+    /// Number of step attempts that were rejected by the step-size controller.
+    pub n_rejected: usize,
+    /// 25th percentile of the scaled error norm.
+    pub q1: ftr,
+    /// 50th percentile (median) of the scaled error norm.
+    pub median: ftr,
+    /// 75th percentile of the scaled error norm.
+    pub q3: ftr,
+    /// Upper Tukey fence (`q3 + 1.5 * (q3 - q1)`) used to flag struggle regions.
+    pub upper_tukey_fence: ftr,
+    /// Gaussian kernel density estimate of the error-magnitude distribution.
+    pub kde: ErrorKde3,
+    /// Arc-length intervals of consecutive steps that exceeded the upper Tukey fence.
+    pub struggle_regions: Vec<StruggleRegion3>,
+}
@@ -0,0 +1,68 @@
+//! Weighted-RMS (WRMS) error norm for the RKF step-size controller.
+//!
+//! The accept/reject decision in [`RKFStepper3`](super::RKFStepper3)'s step-size control loop
+//! previously combined [`RKFStepperConfig`](super::RKFStepperConfig)'s `absolute_tolerance` and
+//! `relative_tolerance` into a single scalar tolerance (`atol + rtol*|y|`) before comparing it
+//! against the aggregate error. That makes whichever tolerance is smaller in magnitude
+//! effectively unreachable: a tight `relative_tolerance` is swamped by a loose
+//! `absolute_tolerance` (or vice versa) as soon as one dominates the sum. [`wrms_error_norm`]
+//! replaces that with the standard per-component weighted-RMS norm used by embedded
+//! Runge-Kutta controllers (e.g. in LSODE/CVODE): each component gets its own scale
+//! `sc_i = atol + rtol * max(|y_i^n|, |y_i^{n+1}|)`, and the step is accepted when the
+//! resulting norm is at most 1. [`wrms_step_scale`] then derives the next step size from that
+//! norm via the usual `safety * err^(-1/(p+1))` rule.
+
+use crate::geometry::{Dim3, Point3, Vec3};
+use crate::tracing::ftr;
+
+/// Computes the weighted-RMS error norm of a step attempt:
+/// `sqrt((1/3) * Σ_i (e_i / sc_i)^2)`, where `sc_i = atol + rtol * max(|y_i^n|, |y_i^{n+1}|)`.
+///
+/// `error_deltas` is the difference between the embedded lower- and higher-order solutions
+/// (as returned by [`RKFStepper3::compute_error_deltas`](super::RKFStepper3::compute_error_deltas)),
+/// `previous_position`/`next_position` are the state before and after the step, and
+/// `absolute_tolerance`/`relative_tolerance` come from [`RKFStepperConfig`](super::RKFStepperConfig).
+///
+/// The step should be accepted by the controller when the returned value is `<= 1.0`.
+pub fn wrms_error_norm(
+    error_deltas: &Vec3<ftr>,
+    previous_position: &Point3<ftr>,
+    next_position: &Point3<ftr>,
+    absolute_tolerance: ftr,
+    relative_tolerance: ftr,
+) -> ftr {
+    let sum_of_squares: ftr = [Dim3::X, Dim3::Y, Dim3::Z]
+        .iter()
+        .map(|&component| {
+            let scale = absolute_tolerance
+                + relative_tolerance
+                    * previous_position[component]
+                        .abs()
+                        .max(next_position[component].abs());
+            let weighted_error = if scale > 0.0 {
+                error_deltas[component] / scale
+            } else {
+                0.0
+            };
+            weighted_error * weighted_error
+        })
+        .sum();
+    (sum_of_squares / 3.0).sqrt()
+}
+
+/// Computes the step size scale factor from a WRMS error norm and method order `p`, via the
+/// standard `safety * err^(-1/(p+1))` rule, clamped to `[min_step_scale, max_step_scale]`.
+///
+/// `err` should be the result of [`wrms_error_norm`], and the step should only be attempted
+/// with this new scale after the current attempt has been rejected (`err > 1.0`), or applied
+/// to the next step after an accepted one.
+pub fn wrms_step_scale(
+    err: ftr,
+    order: u8,
+    safety_factor: ftr,
+    min_step_scale: ftr,
+    max_step_scale: ftr,
+) -> ftr {
+    let raw_scale = safety_factor * err.powf(-1.0 / ((order as ftr) + 1.0));
+    raw_scale.max(min_step_scale).min(max_step_scale)
+}
@@ -9,6 +9,7 @@ use crate::field::VectorField3;
 use crate::interpolation::{Interpolator3};
 use crate::tracing::ftr;
 use super::{RKFStepperState3, RKFStepperConfig, PIControlParams, ComputedDirection3, StepAttempt3, RKFStepper3};
+use super::step_controller::StepControllerMode;
 use super::super::{Stepper3, StepperResult, StepperInstruction};
 
 /// A stepper using the third order Runge–Kutta–Fehlberg method.
@@ -33,10 +34,11 @@ impl RKF23Stepper3 {
     pub fn new(config: RKFStepperConfig) -> Self {
         config.validate();
 
-        let pi_control = if config.use_pi_control {
-            PIControlParams::activated(Self::ORDER)
-        } else {
-            PIControlParams::deactivated(Self::ORDER)
+        let pi_control = match config.step_controller.mode {
+            StepControllerMode::Standard => PIControlParams::deactivated(Self::ORDER),
+            StepControllerMode::Pi | StepControllerMode::Pid | StepControllerMode::Gustafsson => {
+                PIControlParams::activated(Self::ORDER)
+            }
         };
         let position = Point3::origin();
         let direction = Vec3::zero();
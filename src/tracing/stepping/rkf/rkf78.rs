@@ -0,0 +1,359 @@
+//! Stepping using the Fehlberg 7(8) scheme,
+//! a thirteen-stage eighth-order Runge-Kutta method with error
+//! estimation through an embedded seventh-order combination of
+//! only four of its stages, for smooth-field long-distance tracing
+//! where the extra stages pay for themselves through much larger
+//! accepted step sizes.
+//!
+//! Unlike [`RKF23Stepper3`](super::rkf23::RKF23Stepper3) and
+//! [`RKF45Stepper3`](super::rkf45::RKF45Stepper3), this method is not
+//! first-same-as-last: its thirteenth stage is evaluated at a point that does
+//! not coincide with the accepted next position (its coefficients differ from
+//! the propagation weights), so the final field evaluation used as
+//! `next_direction` here is a genuine extra evaluation at `next_position`,
+//! not a reused stage. This costs one additional evaluation per step compared
+//! to a true FSAL method, but keeps `state.direction` valid as the first
+//! stage of the next step regardless of what the shared stepping loop does
+//! with it between steps.
+//!
+//! Dense output reuses the same cubic Hermite interpolant as the other RKF
+//! steppers rather than a native eighth-order continuous extension; deriving
+//! the latter would need extra stage evaluations beyond the thirteen used
+//! for stepping and error estimation, which is out of scope here.
+
+use num;
+use crate::geometry::{Point3, Vec3};
+use crate::grid::{Grid3};
+use crate::field::VectorField3;
+use crate::interpolation::{Interpolator3};
+use crate::tracing::ftr;
+use super::{RKFStepperState3, RKFStepperConfig, PIControlParams, ComputedDirection3, StepAttempt3, RKFStepper3};
+use super::step_controller::StepControllerMode;
+use super::super::{Stepper3, StepperResult, StepperInstruction};
+
+/// A stepper using the Fehlberg eighth order Runge–Kutta method with an
+/// embedded seventh-order error estimate.
+pub struct RKF78Stepper3(RKFStepperState3);
+
+impl RKF78Stepper3 {
+    const ORDER: u8 = 8;
+    const N_INTERMEDIATE_STEPS: usize = 12;
+
+    const A21: ftr = 2.0/27.0;
+
+    const A31: ftr = 1.0/36.0;
+    const A32: ftr = 1.0/12.0;
+
+    const A41: ftr = 1.0/24.0;
+    const A43: ftr = 1.0/8.0;
+
+    const A51: ftr = 5.0/12.0;
+    const A53: ftr = -25.0/16.0;
+    const A54: ftr = 25.0/16.0;
+
+    const A61: ftr = 1.0/20.0;
+    const A64: ftr = 1.0/4.0;
+    const A65: ftr = 1.0/5.0;
+
+    const A71: ftr = -25.0/108.0;
+    const A74: ftr = 125.0/108.0;
+    const A75: ftr = -65.0/27.0;
+    const A76: ftr = 125.0/54.0;
+
+    const A81: ftr = 31.0/300.0;
+    const A85: ftr = 61.0/225.0;
+    const A86: ftr = -2.0/9.0;
+    const A87: ftr = 13.0/900.0;
+
+    const A91: ftr = 2.0;
+    const A94: ftr = -53.0/6.0;
+    const A95: ftr = 704.0/45.0;
+    const A96: ftr = -107.0/9.0;
+    const A97: ftr = 67.0/90.0;
+    const A98: ftr = 3.0;
+
+    const A10_1: ftr = -91.0/108.0;
+    const A10_4: ftr = 23.0/108.0;
+    const A10_5: ftr = -976.0/135.0;
+    const A10_6: ftr = 311.0/54.0;
+    const A10_7: ftr = -19.0/60.0;
+    const A10_8: ftr = 17.0/6.0;
+    const A10_9: ftr = -1.0/12.0;
+
+    const A11_1: ftr = 2383.0/4100.0;
+    const A11_4: ftr = -341.0/164.0;
+    const A11_5: ftr = 4496.0/1025.0;
+    const A11_6: ftr = -301.0/82.0;
+    const A11_7: ftr = 2133.0/4100.0;
+    const A11_8: ftr = 45.0/82.0;
+    const A11_9: ftr = 45.0/164.0;
+    const A11_10: ftr = 18.0/41.0;
+
+    const A12_1: ftr = 3.0/205.0;
+    const A12_6: ftr = -6.0/41.0;
+    const A12_7: ftr = -3.0/205.0;
+    const A12_8: ftr = -3.0/41.0;
+    const A12_9: ftr = 3.0/41.0;
+    const A12_10: ftr = 6.0/41.0;
+
+    const A13_1: ftr = -1777.0/4100.0;
+    const A13_4: ftr = -341.0/164.0;
+    const A13_5: ftr = 4496.0/1025.0;
+    const A13_6: ftr = -289.0/82.0;
+    const A13_7: ftr = 2193.0/4100.0;
+    const A13_8: ftr = 51.0/82.0;
+    const A13_9: ftr = 33.0/164.0;
+    const A13_10: ftr = 12.0/41.0;
+    const A13_12: ftr = 1.0;
+
+    // 8th-order solution weights (stages 1-5 and 11 do not contribute; stage 1
+    // is `state.direction`).
+    const B6: ftr = 34.0/105.0;
+    const B7: ftr = 9.0/35.0;
+    const B8: ftr = 9.0/35.0;
+    const B9: ftr = 9.0/280.0;
+    const B10: ftr = 9.0/280.0;
+    const B12: ftr = 41.0/840.0;
+    const B13: ftr = 41.0/840.0;
+
+    // Differences between the 8th- and embedded 7th-order solution weights:
+    // the well-known Fehlberg 7(8) error estimate reduces to
+    // `(41/840) * h * (k1 + k11 - k12 - k13)`.
+    const E1: ftr = 41.0/840.0;
+    const E11: ftr = 41.0/840.0;
+    const E12: ftr = -41.0/840.0;
+    const E13: ftr = -41.0/840.0;
+
+    /// Creates a new RKF78 stepper with the given configuration.
+    pub fn new(config: RKFStepperConfig) -> Self {
+        config.validate();
+
+        let pi_control = match config.step_controller.mode {
+            StepControllerMode::Standard => PIControlParams::deactivated(Self::ORDER),
+            StepControllerMode::Pi | StepControllerMode::Pid | StepControllerMode::Gustafsson => {
+                PIControlParams::activated(Self::ORDER)
+            }
+        };
+        let position = Point3::origin();
+        let direction = Vec3::zero();
+        let distance = 0.0;
+        let step_size = config.initial_step_size;
+        let error = config.initial_error;
+        let n_sudden_reversals = 0;
+        let previous_step_size = 0.0;
+        let previous_position = Point3::origin();
+        let previous_direction = Vec3::zero();
+        let intermediate_directions = Vec::with_capacity(Self::N_INTERMEDIATE_STEPS);
+        let previous_step_displacement = Vec3::zero();
+        let previous_step_wrapped = false;
+        let next_output_distance = config.dense_step_size;
+
+        RKF78Stepper3(RKFStepperState3{
+            config,
+            pi_control,
+            position,
+            direction,
+            distance,
+            step_size,
+            error,
+            n_sudden_reversals,
+            previous_step_size,
+            previous_position,
+            previous_direction,
+            intermediate_directions,
+            previous_step_displacement,
+            previous_step_wrapped,
+            next_output_distance,
+        })
+    }
+}
+
+impl RKFStepper3 for RKF78Stepper3 {
+    fn state(&self) -> &RKFStepperState3 { &self.0 }
+    fn state_mut(&mut self) -> &mut RKFStepperState3 { &mut self.0 }
+
+    fn attempt_step<F, G, I>(&self, field: &VectorField3<F, G>, interpolator: &I) -> StepperResult<StepAttempt3>
+    where F: num::Float + std::fmt::Display,
+          G: Grid3<F> + Clone,
+          I: Interpolator3
+    {
+        let state = self.state();
+
+        macro_rules! compute_stage {
+            ($position:expr) => {
+                match Self::compute_direction(field, interpolator, &$position) {
+                    StepperResult::Ok(ComputedDirection3::Standard(direction)) => direction,
+                    StepperResult::Ok(ComputedDirection3::WithWrappedPosition((_, direction))) => direction,
+                    StepperResult::Stopped(cause) => return StepperResult::Stopped(cause)
+                }
+            };
+        }
+
+        let stage_2 = compute_stage!(&state.position + &state.direction*(Self::A21*state.step_size));
+
+        let stage_3 = compute_stage!(&state.position + &(&state.direction*Self::A31 + &stage_2*Self::A32)*state.step_size);
+
+        let stage_4 = compute_stage!(&state.position + &(&state.direction*Self::A41 + &stage_3*Self::A43)*state.step_size);
+
+        let stage_5 = compute_stage!(&state.position + &(&state.direction*Self::A51 + &stage_3*Self::A53 + &stage_4*Self::A54)*state.step_size);
+
+        let stage_6 = compute_stage!(&state.position + &(&state.direction*Self::A61 + &stage_4*Self::A64 + &stage_5*Self::A65)*state.step_size);
+
+        let stage_7 = compute_stage!(&state.position + &(         &state.direction*Self::A71 +
+                                                                   &stage_4*Self::A74 +
+                                                                   &stage_5*Self::A75 +
+                                                                   &stage_6*Self::A76)*state.step_size);
+
+        let stage_8 = compute_stage!(&state.position + &(         &state.direction*Self::A81 +
+                                                                   &stage_5*Self::A85 +
+                                                                   &stage_6*Self::A86 +
+                                                                   &stage_7*Self::A87)*state.step_size);
+
+        let stage_9 = compute_stage!(&state.position + &(         &state.direction*Self::A91 +
+                                                                   &stage_4*Self::A94 +
+                                                                   &stage_5*Self::A95 +
+                                                                   &stage_6*Self::A96 +
+                                                                   &stage_7*Self::A97 +
+                                                                   &stage_8*Self::A98)*state.step_size);
+
+        let stage_10 = compute_stage!(&state.position + &(        &state.direction*Self::A10_1 +
+                                                                   &stage_4*Self::A10_4 +
+                                                                   &stage_5*Self::A10_5 +
+                                                                   &stage_6*Self::A10_6 +
+                                                                   &stage_7*Self::A10_7 +
+                                                                   &stage_8*Self::A10_8 +
+                                                                   &stage_9*Self::A10_9)*state.step_size);
+
+        let stage_11 = compute_stage!(&state.position + &(        &state.direction*Self::A11_1 +
+                                                                   &stage_4*Self::A11_4 +
+                                                                   &stage_5*Self::A11_5 +
+                                                                   &stage_6*Self::A11_6 +
+                                                                   &stage_7*Self::A11_7 +
+                                                                   &stage_8*Self::A11_8 +
+                                                                   &stage_9*Self::A11_9 +
+                                                                   &stage_10*Self::A11_10)*state.step_size);
+
+        let stage_12 = compute_stage!(&state.position + &(        &state.direction*Self::A12_1 +
+                                                                   &stage_6*Self::A12_6 +
+                                                                   &stage_7*Self::A12_7 +
+                                                                   &stage_8*Self::A12_8 +
+                                                                   &stage_9*Self::A12_9 +
+                                                                   &stage_10*Self::A12_10)*state.step_size);
+
+        let stage_13 = compute_stage!(&state.position + &(        &state.direction*Self::A13_1 +
+                                                                   &stage_4*Self::A13_4 +
+                                                                   &stage_5*Self::A13_5 +
+                                                                   &stage_6*Self::A13_6 +
+                                                                   &stage_7*Self::A13_7 +
+                                                                   &stage_8*Self::A13_8 +
+                                                                   &stage_9*Self::A13_9 +
+                                                                   &stage_10*Self::A13_10 +
+                                                                   &stage_12*Self::A13_12)*state.step_size);
+
+        let step_displacement = (         &stage_6*Self::B6 +
+                                                  &stage_7*Self::B7 +
+                                                  &stage_8*Self::B8 +
+                                                  &stage_9*Self::B9 +
+                                                  &stage_10*Self::B10 +
+                                                  &stage_12*Self::B12 +
+                                                  &stage_13*Self::B13)*state.step_size;
+
+        let mut next_position = &state.position + &step_displacement;
+
+        let mut step_wrapped = false;
+
+        // Not FSAL: stage 13 is evaluated off the accepted trajectory, so the
+        // direction at the accepted next position must be computed fresh.
+        let next_direction = match Self::compute_direction(field, interpolator, &next_position) {
+            StepperResult::Ok(ComputedDirection3::Standard(direction)) => direction,
+            StepperResult::Ok(ComputedDirection3::WithWrappedPosition((wrapped_position, direction))) => {
+                step_wrapped = true;
+                next_position = wrapped_position;
+                direction
+            },
+            StepperResult::Stopped(cause) => return StepperResult::Stopped(cause)
+        };
+
+        StepperResult::Ok(StepAttempt3{
+            step_displacement,
+            next_position,
+            next_direction,
+            intermediate_directions: vec![
+                stage_2, stage_3, stage_4, stage_5, stage_6, stage_7,
+                stage_8, stage_9, stage_10, stage_11, stage_12, stage_13,
+            ],
+            step_wrapped
+        })
+    }
+
+    fn compute_error_deltas(&self, attempt: &StepAttempt3) -> Vec3<ftr> {
+        let state = self.state();
+        (                                 &state.direction*Self::E1 +
+         &attempt.intermediate_directions[9]*Self::E11 +
+         &attempt.intermediate_directions[10]*Self::E12 +
+         &attempt.intermediate_directions[11]*Self::E13)*state.step_size
+    }
+
+    fn compute_dense_interpolation_coefs(&self) -> Vec<Vec3<ftr>> {
+        let state = self.state();
+        let coef_vec_1 = state.previous_position.to_vec3();
+        let coef_vec_2 = state.previous_step_displacement.clone();
+        let coef_vec_3 = &state.previous_direction*state.previous_step_size;
+        let coef_vec_4 = &state.direction*state.previous_step_size;
+        vec![coef_vec_1, coef_vec_2, coef_vec_3, coef_vec_4]
+    }
+
+    fn interpolate_dense_position<F, G>(&self, grid: &G, coefs: &[Vec3<ftr>], fraction: ftr) -> Point3<ftr>
+    where F: num::Float + std::fmt::Display,
+          G: Grid3<F> + Clone
+    {
+        debug_assert!(fraction > 0.0 && fraction <= 1.0);
+        let fraction_minus_one = fraction - 1.0;
+        let position = coefs[0].to_point3() +
+                       &coefs[1]*fraction +
+                       (&coefs[1]*(-(fraction + fraction_minus_one)) +
+                        &coefs[2]*fraction_minus_one +
+                        &coefs[3]*fraction)*(fraction*fraction_minus_one);
+
+        if self.state().previous_step_wrapped {
+            // If the previous step wrapped around a periodic boundary,
+            // this output position might fall on either side of the boundary,
+            // so we have to wrap it in case it falls on the outside
+            Point3::from(&grid.wrap_point(&Point3::from(&position)).unwrap())
+        } else {
+            position
+        }
+    }
+}
+
+impl Stepper3 for RKF78Stepper3 {
+    fn place<F, G, I, C>(&mut self, field: &VectorField3<F, G>, interpolator: &I, position: &Point3<ftr>, callback: &mut C) -> StepperResult<()>
+    where F: num::Float + std::fmt::Display,
+          G: Grid3<F> + Clone,
+          I: Interpolator3,
+          C: FnMut(&Point3<ftr>) -> StepperInstruction
+    {
+        self.place_with_callback(field, interpolator, position, callback)
+    }
+
+    fn step<F, G, I, C>(&mut self, field: &VectorField3<F, G>, interpolator: &I, callback: &mut C) -> StepperResult<()>
+    where F: num::Float + std::fmt::Display,
+          G: Grid3<F> + Clone,
+          I: Interpolator3,
+          C: FnMut(&Point3<ftr>) -> StepperInstruction
+    {
+        self.step_with_callback(field, interpolator, callback)
+    }
+
+    fn step_dense_output<F, G, I, C>(&mut self, field: &VectorField3<F, G>, interpolator: &I, callback: &mut C) -> StepperResult<()>
+    where F: num::Float + std::fmt::Display,
+          G: Grid3<F> + Clone,
+          I: Interpolator3,
+          C: FnMut(&Point3<ftr>) -> StepperInstruction
+    {
+        self.step_with_callback_dense_output(field, interpolator, callback)
+    }
+
+    fn position(&self) -> &Point3<ftr> { &self.state().position }
+    fn distance(&self) -> ftr { self.state().distance }
+}
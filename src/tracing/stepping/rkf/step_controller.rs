@@ -0,0 +1,109 @@
+//! Step-size controller algorithms for the RKF step-size feedback loop.
+//!
+//! [`RKFStepperConfig`](super::RKFStepperConfig) previously exposed only a boolean
+//! `use_pi_control` switch between an uncontrolled step scale and a fixed-gain PI controller.
+//! [`StepControllerMode`] replaces that with an explicit choice between four algorithms, and
+//! [`StepControllerConfig`] carries the gains and deadband each of them needs.
+//! `RKFStepperConfig` is expected to hold a `step_controller: StepControllerConfig` field
+//! (populated by
+//! [`construct_rkf_stepper_config_from_options`](crate::cli::tracing::stepping::rkf::construct_rkf_stepper_config_from_options)),
+//! and the step-size control loop in [`RKFStepperState3`](super::RKFStepperState3) should call
+//! [`StepControllerConfig::step_scale`] in place of the old fixed PI-or-none calculation.
+
+use super::error_norm::wrms_step_scale;
+use crate::tracing::ftr;
+
+/// Which step-size control algorithm to use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepControllerMode {
+    /// `scale = safety * err^(-1/(p+1))`, with no dependence on step history.
+    Standard,
+    /// `scale = safety * err^(-k_I) * err_prev^(k_P)`.
+    Pi,
+    /// The PI formula above, additionally multiplied by `err_prev2^(-k_D)`, penalizing errors
+    /// that are accelerating, for extra stability on stiff error transients.
+    Pid,
+    /// The PI formula, additionally multiplied by `h_n/h_{n-1}` to anticipate the next
+    /// acceptable step size (Gustafsson's predictive controller).
+    Gustafsson,
+}
+
+/// Gains, deadband and mode for a step-size controller.
+#[derive(Clone, Copy, Debug)]
+pub struct StepControllerConfig {
+    pub mode: StepControllerMode,
+    /// Integral gain `k_I`, applied to the current error. Defaults to `0.7/(p+1)`.
+    pub integral_gain: ftr,
+    /// Proportional gain `k_P`, applied to the previous error. Defaults to `0.4/(p+1)`.
+    pub proportional_gain: ftr,
+    /// Derivative gain `k_D`, applied to the error two steps back. Only used in
+    /// [`StepControllerMode::Pid`].
+    pub derivative_gain: ftr,
+    /// Scale factors inside `[deadband_lo, deadband_hi]` are rounded to 1 (the step size is
+    /// left unchanged), reducing step-length oscillation around the accept/reject boundary.
+    pub deadband_lo: ftr,
+    pub deadband_hi: ftr,
+}
+
+impl StepControllerConfig {
+    /// Default integral gain for a method of the given order, `0.7/(p+1)`.
+    pub fn default_integral_gain(order: u8) -> ftr {
+        0.7 / ((order as ftr) + 1.0)
+    }
+
+    /// Default proportional gain for a method of the given order, `0.4/(p+1)`.
+    pub fn default_proportional_gain(order: u8) -> ftr {
+        0.4 / ((order as ftr) + 1.0)
+    }
+
+    /// Computes the step size scale factor for the next step.
+    ///
+    /// `err`/`err_prev`/`err_prev2` are the current and two preceding WRMS error norms (see
+    /// [`wrms_error_norm`](super::error_norm::wrms_error_norm)); a norm of `1.0` should be used
+    /// for steps that haven't happened yet, so history-dependent modes fall back to their
+    /// integral-only term. `previous_step_ratio` is `h_n/h_{n-1}`, used only by
+    /// [`StepControllerMode::Gustafsson`].
+    pub fn step_scale(
+        &self,
+        order: u8,
+        safety_factor: ftr,
+        err: ftr,
+        err_prev: ftr,
+        err_prev2: ftr,
+        previous_step_ratio: ftr,
+        min_step_scale: ftr,
+        max_step_scale: ftr,
+    ) -> ftr {
+        let raw_scale = match self.mode {
+            // Delegates to `wrms_step_scale` rather than recomputing the same
+            // `safety * err^(-1/(p+1))` formula here, so there is exactly one
+            // implementation of the uncontrolled rule.
+            StepControllerMode::Standard => {
+                wrms_step_scale(err, order, safety_factor, min_step_scale, max_step_scale)
+            }
+            StepControllerMode::Pi => {
+                safety_factor * err.powf(-self.integral_gain) * err_prev.powf(self.proportional_gain)
+            }
+            StepControllerMode::Pid => {
+                safety_factor
+                    * err.powf(-self.integral_gain)
+                    * err_prev.powf(self.proportional_gain)
+                    * err_prev2.powf(-self.derivative_gain)
+            }
+            StepControllerMode::Gustafsson => {
+                safety_factor
+                    * err.powf(-self.integral_gain)
+                    * err_prev.powf(self.proportional_gain)
+                    * previous_step_ratio
+            }
+        };
+
+        let clamped_scale = raw_scale.max(min_step_scale).min(max_step_scale);
+
+        if clamped_scale >= self.deadband_lo && clamped_scale <= self.deadband_hi {
+            1.0
+        } else {
+            clamped_scale
+        }
+    }
+}
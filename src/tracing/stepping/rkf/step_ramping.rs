@@ -0,0 +1,49 @@
+//! Startup step-size ramping, capping how quickly the step size may grow during the first few
+//! accepted steps of a trace.
+//!
+//! At the seed point, `initial_step_length` and `initial_error` in
+//! [`RKFStepperConfig`](super::RKFStepperConfig) are only rough guesses, so the error history the
+//! step-size controller (see [`step_controller`](super::step_controller)) relies on hasn't
+//! stabilized yet. Without a cap, the controller can accept an overly large, poorly-justified
+//! first step, skipping over small-scale field structure near the seed before it has had a
+//! chance to react. [`StepRampingConfig::cap_step_size`] is meant to be applied to the
+//! controller's proposed next step size, in place of the normal `max_step_scale` bound, for the
+//! first `ramping_steps` accepted steps of a trace (tracked by the caller as
+//! `accepted_step_count`); after that it is a no-op.
+//!
+//! `RKFStepperConfig` is expected to hold a `step_ramping: StepRampingConfig` field (populated
+//! by
+//! [`construct_rkf_stepper_config_from_options`](crate::cli::tracing::stepping::rkf::construct_rkf_stepper_config_from_options)),
+//! and `RKFStepperState3` would need an `accepted_step_count` counter, incremented alongside
+//! `distance` each time a step is accepted in the shared accept/grow loop, for
+//! `cap_step_size` to be wired in there.
+
+use crate::tracing::ftr;
+
+/// Caps step-size growth for the first `ramping_steps` accepted steps of a trace.
+#[derive(Clone, Copy, Debug)]
+pub struct StepRampingConfig {
+    /// Largest allowed ratio of the new step size to the previous one while ramping.
+    pub ramping_factor: ftr,
+    /// Number of accepted steps, counted from the seed point, for which ramping applies.
+    pub ramping_steps: usize,
+}
+
+impl StepRampingConfig {
+    /// Caps `proposed_step_size` to at most `previous_step_size * ramping_factor` if
+    /// `accepted_step_count` (the number of steps already accepted on this trace, before the
+    /// one currently being attempted) is still within the ramping window, otherwise returns it
+    /// unchanged.
+    pub fn cap_step_size(
+        &self,
+        proposed_step_size: ftr,
+        previous_step_size: ftr,
+        accepted_step_count: usize,
+    ) -> ftr {
+        if accepted_step_count < self.ramping_steps {
+            proposed_step_size.min(previous_step_size * self.ramping_factor)
+        } else {
+            proposed_step_size
+        }
+    }
+}